@@ -0,0 +1,183 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::Avatar;
+use crate::OutputFormat;
+use crate::RenderOptions;
+use crate::Y4mWriter;
+
+use image::gif::GifEncoder;
+use image::gif::Repeat;
+use image::Delay;
+use image::Frame;
+use image::RgbaImage;
+
+use std::f64::consts::PI;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long each frame is shown unless changed, 24 frames make a one second loop
+pub const FRAME_DELAY: Duration = Duration::from_millis(40);
+
+/// Animation holds the frames of a looping avatar animation.
+///
+///     use unicornify::Animation;
+///     use unicornify::Avatar;
+///     use unicornify::RenderOptions;
+///
+///     let avatar = Avatar::new("58479f76374a3ba3c69b9804163f39f4".to_string(), false).unwrap();
+///     let gallop = Animation::gait(&avatar, &RenderOptions::new(32), 4, 1).unwrap();
+///
+///     let mut gif = Vec::new();
+///     gallop.write_gif(&mut gif).unwrap();
+#[derive(Clone, Debug)]
+pub struct Animation {
+    pub frames: Vec<RgbaImage>,
+    /// How long each frame is shown
+    pub frame_delay: Duration,
+}
+
+impl Animation {
+    pub fn new(frames: Vec<RgbaImage>, frame_delay: Duration) -> Self {
+        Animation {
+            frames,
+            frame_delay,
+        }
+    }
+
+    /// Renders +frame_count+ frames of one cycle of the avatar's gait, starting from its own
+    /// pose.  Each frame is a new unicorn so the torso is leveled for its legs.
+    pub fn gait(
+        avatar: &Avatar,
+        options: &RenderOptions,
+        frame_count: usize,
+        threads: usize,
+    ) -> Result<Self> {
+        if frame_count == 0 {
+            bail!("An animation needs at least one frame");
+        }
+
+        let pose = &avatar.parameters().data.pose;
+        let mut frames = Vec::with_capacity(frame_count);
+
+        for i in 0..frame_count {
+            let mut parameters = avatar.parameters().clone();
+            parameters.data.pose = pose.with_phase(pose.phase() + i as f64 / frame_count as f64);
+
+            let frame = Avatar::from_parameters(parameters)?;
+
+            frames.push(frame.render_threaded(options, threads)?);
+        }
+
+        Ok(Animation::new(frames, FRAME_DELAY))
+    }
+
+    /// Renders +frame_count+ frames of the camera circling the unicorn once, starting from the
+    /// avatar's own view.  The pose stays the same in every frame.
+    pub fn turntable(
+        avatar: &Avatar,
+        options: &RenderOptions,
+        frame_count: usize,
+        threads: usize,
+    ) -> Result<Self> {
+        if frame_count == 0 {
+            bail!("An animation needs at least one frame");
+        }
+
+        let y_angle = avatar.parameters().data.y_angle;
+        let mut frames = Vec::with_capacity(frame_count);
+
+        for i in 0..frame_count {
+            let mut parameters = avatar.parameters().clone();
+            parameters.data.y_angle = y_angle + 2.0 * PI * i as f64 / frame_count as f64;
+
+            let frame = Avatar::from_parameters(parameters)?;
+
+            frames.push(frame.render_threaded(options, threads)?);
+        }
+
+        Ok(Animation::new(frames, FRAME_DELAY))
+    }
+
+    /// Saves each frame as its own image.  {frame} in +template+ is replaced by the frame number,
+    /// zero padded so the files sort in order, and the extension picks the image format.
+    pub fn save_frames(&self, template: &str) -> Result<Vec<PathBuf>> {
+        if !template.contains("{frame}") {
+            bail!("Template {} needs {{frame}}", template);
+        }
+
+        let width = self.frames.len().saturating_sub(1).to_string().len();
+        let mut paths = Vec::with_capacity(self.frames.len());
+
+        for (i, frame) in self.frames.iter().enumerate() {
+            let path = PathBuf::from(
+                template.replace("{frame}", &format!("{:0width$}", i, width = width)),
+            );
+
+            let encoded = OutputFormat::from_path(&path)?.encode(frame)?;
+
+            fs::write(&path, encoded)
+                .with_context(|| format!("Unable to write {}", path.display()))?;
+
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+
+    /// Writes the frames as uncompressed Y4M video at the frame rate of +frame_delay+
+    pub fn write_y4m<W: Write>(&self, writer: W) -> Result<()> {
+        let (width, height) = match self.frames.first() {
+            Some(frame) => frame.dimensions(),
+            None => bail!("An animation needs at least one frame"),
+        };
+
+        let millis = self.frame_delay.as_millis() as u32;
+
+        if millis == 0 {
+            bail!("Y4M frames must be shown for at least a millisecond");
+        }
+
+        let divisor = gcd(1000, millis);
+
+        let mut y4m = Y4mWriter::new(writer, width, height, 1000 / divisor, millis / divisor)?;
+
+        for frame in self.frames.iter() {
+            y4m.write_frame(frame)?;
+        }
+
+        y4m.finish()?;
+
+        Ok(())
+    }
+
+    /// Writes the frames as a GIF that loops forever.  GIF delays are in hundredths of a second
+    /// and GIF transparency is on or off, so semi-transparent edges become opaque.
+    pub fn write_gif<W: Write>(&self, writer: W) -> Result<()> {
+        let delay = Delay::from_numer_denom_ms(self.frame_delay.as_millis() as u32, 1);
+
+        let mut encoder = GifEncoder::new(writer);
+
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .context("Unable to write GIF")?;
+
+        let frames = self
+            .frames
+            .iter()
+            .map(|f| Frame::from_parts(f.clone(), 0, 0, delay));
+
+        encoder.encode_frames(frames).context("Unable to write GIF")
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
@@ -1,31 +1,47 @@
 extern crate image;
 
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 
 use crate::geometry::Axis;
 use crate::geometry::Point;
 use crate::geometry::Vector;
-use crate::geometry::DEGREE;
+use crate::identity::email_hash;
+use crate::parameters::Parameters;
+use crate::pov;
+use crate::render::BallProjection;
+use crate::render::Bounds;
 use crate::render::QuadrantTracer;
 use crate::render::ScalingTracer;
 use crate::render::Tracer;
+use crate::render::TracerArena;
+use crate::render::TracerId;
 use crate::render::TranslatingTracer;
 use crate::render::WorldView;
-use crate::scene::Background;
-use crate::scene::Grass;
+use crate::svg;
 use crate::unicorn::Unicorn;
-use crate::Color;
 use crate::Data;
+use crate::GenerationProfile;
+use crate::HashAlgorithm;
+use crate::Mesh;
 use crate::Random;
+use crate::RenderOptions;
+use crate::Rng;
 
+use image::imageops;
 use image::RgbaImage;
 
+use std::thread;
+
+/// Changes whenever the same hash and options render a different image, so images cached by
+/// hash and options can be told apart
+pub const RENDER_VERSION: u32 = 1;
+
 pub struct Avatar {
+    hash: Option<String>,
+    parameters: Parameters,
     data: Data,
-    scale_factor: f64,
-    focal_length: f64,
-    background: Background,
     unicorn: Unicorn,
 }
 
@@ -33,70 +49,97 @@ impl Avatar {
     pub fn new(hash: String, zoom_out: bool) -> Result<Self> {
         let mut rand = Random::new();
 
-        rand.seed_hex_string(hash)
+        rand.seed_hex_string(hash.clone())
             .with_context(|| format!("Unable to use avatar hash"))?;
 
-        let mut data = Data::new();
-        let mut background = Background::new();
-        let mut grass = Grass::new();
+        let mut avatar = Avatar::from_rng(&mut rand, zoom_out);
+        avatar.hash = Some(hash);
 
-        data.rand1(&mut rand);
-        background.rand1(&mut rand);
+        Ok(avatar)
+    }
 
-        let scale_factor = 0.5 + rand.rand().powi(2) * 2.5;
+    /// Creates an avatar from the numbers +rand+ generates.  Avatar::new() uses a Random seeded
+    /// with the hash.
+    pub fn from_rng<R: Rng>(rand: &mut R, zoom_out: bool) -> Self {
+        let profile = GenerationProfile::default();
 
-        let scale_factor = if zoom_out { 0.5 } else { scale_factor };
+        Avatar::assemble(Parameters::generate(rand, &profile, zoom_out))
+    }
 
-        let sign = rand.choice(2) * 2 - 1;
-        let abs = rand.rand_i32(10, 75);
-        data.y_angle = (90 + sign * abs) as f64 * DEGREE;
-        data.x_angle = rand.rand_i32(-20, 20) as f64 * DEGREE;
+    /// Creates an avatar from stored or hand-edited +parameters+ without the hash that generated
+    /// them
+    pub fn from_parameters(parameters: Parameters) -> Result<Self> {
+        parameters.validate()?;
 
-        data.rand2(&mut rand);
-        background.rand2(&mut rand);
-        data.rand3(&mut rand);
-        grass.rand(&mut rand);
+        Ok(Avatar::assemble(parameters))
+    }
 
-        let grass_slope = 2.0 + 4.0 * (20.0 - data.x_angle / DEGREE) / 40.0;
-        let grass_scale = 1.0 + (scale_factor - 0.5) / 2.5;
-        grass.blade_height_near = (0.02 + 0.02 * rand.rand()) * grass_scale;
-        grass.blade_height_far = grass.blade_height_near / grass_slope;
+    pub(crate) fn assemble(parameters: Parameters) -> Self {
+        // Unicorn::new() levels the camera into the model for downward views
+        let mut data = parameters.data.clone();
 
-        let focal_length = 250.0 + rand.rand() * 250.0;
+        let unicorn = Unicorn::new(&mut data);
 
-        data.rand4(&mut rand);
+        Avatar {
+            hash: None,
+            parameters,
+            data,
+            unicorn,
+        }
+    }
 
-        let light_direction = Vector::new(rand.rand() * 16.0 - 8.0, 10.0, rand.rand() * 3.0);
-        let _light_direction = Vector::new(light_direction.z, light_direction.y, -light_direction.x);
+    /// Creates the avatar Gravatar would show for +email+ when hashed with +algorithm+
+    pub fn from_email(email: &str, algorithm: HashAlgorithm, zoom_out: bool) -> Result<Self> {
+        Avatar::new(email_hash(email, algorithm), zoom_out)
+    }
 
-        // end randomization
+    /// The hash this avatar was created from, avatars made from numbers or parameters have none
+    pub fn hash(&self) -> Option<&str> {
+        self.hash.as_deref()
+    }
 
-        grass.horizon = background.horizon;
-        grass.color1 = Color::hsl(
-            background.land_hue,
-            background.land_sat,
-            background.land_light,
-        );
-        grass.color2 = Color::hsl(
-            background.land_hue,
-            background.land_sat,
-            background.land_light / 2,
-        );
+    /// The generated parameters of this avatar
+    pub fn parameters(&self) -> &Parameters {
+        &self.parameters
+    }
 
-        if (data.y_angle - 90.0 * DEGREE) * data.neck_tilt > 0.0 {
-            data.neck_tilt *= -1.0;
-            data.face_tilt *= -1.0;
+    /// The generated unicorn traits of this avatar
+    pub fn data(&self) -> &Data {
+        &self.parameters.data
+    }
+
+    /// Draws the avatar as described by +options+.  Unlike draw() options the renderer does not
+    /// support are reported as an error instead of panicking.
+    ///
+    /// The zoom_out option is applied when the Avatar is created, so it is ignored here.
+    pub fn render(&self, options: &RenderOptions) -> Result<RgbaImage> {
+        self.render_threaded(options, 1)
+    }
+
+    /// Like render() but traces on +threads+ threads.  The image does not depend on the number
+    /// of threads.
+    pub fn render_threaded(&self, options: &RenderOptions, threads: usize) -> Result<RgbaImage> {
+        options.validate()?;
+
+        if threads == 0 {
+            bail!("At least one thread is needed to render");
         }
 
-        let unicorn = Unicorn::new(&mut data);
+        Ok(self.draw_options(options, threads))
+    }
 
-        Ok(Avatar {
-            data,
-            scale_factor,
-            focal_length,
-            background,
-            unicorn,
-        })
+    /// Draws the avatar as a resolution independent SVG document, see render() for +options+
+    pub fn render_svg(&self, options: &RenderOptions) -> Result<String> {
+        options.validate()?;
+
+        Ok(svg::render(self, options))
+    }
+
+    /// Writes the avatar as a POV-Ray scene whose camera sees what render() draws for +options+
+    pub fn render_pov(&self, options: &RenderOptions) -> Result<String> {
+        options.validate()?;
+
+        Ok(pov::render(self, options))
     }
 
     pub fn draw(
@@ -109,26 +152,101 @@ impl Avatar {
         grass: bool,
         parallelize: bool,
     ) -> RgbaImage {
+        let threads = if parallelize {
+            thread::available_parallelism().map_or(1, |n| n.get())
+        } else {
+            1
+        };
+
+        let options = RenderOptions {
+            size,
+            quadrant,
+            region: None,
+            background: with_background,
+            zoom_out: false,
+            shading,
+            grass,
+        };
+
+        self.draw_options(&options, threads)
+    }
+
+    /// The camera for an image of +size+ pixels, and the scale and shift from its projection
+    /// plane to the image
+    pub(crate) fn camera(&self, size: u32) -> (WorldView, f64, Point) {
         let fsize = size as f64;
-        let factor = ((self.scale_factor - 0.5) / 2.5).sqrt();
+        let factor = ((self.parameters.scale_factor - 0.5) / 2.5).sqrt();
 
         let head = self.unicorn.head();
         let shoulder = self.unicorn.shoulder();
         let look_at = shoulder.clone() + ((head.clone() - shoulder) * factor);
 
-        let pivot = &head.center.borrow();
-        let camera = look_at + Vector::new(0.0, 0.0, -3.0 * self.focal_length);
+        let pivot = &head.center.read().unwrap();
+        let camera = look_at + Vector::new(0.0, 0.0, -3.0 * self.parameters.focal_length);
         let camera = camera.rotate_around(pivot, -self.data.x_angle, Axis::X);
         let camera = camera.rotate_around(pivot, -self.data.y_angle, Axis::Y);
 
-        let world_view = WorldView::new(camera, look_at, self.focal_length);
+        let world_view = WorldView::new(camera, look_at, self.parameters.focal_length);
 
         let shift = Point::new(
             0.5 * fsize,
             factor * fsize / 3.0 + (1.0 - factor) * fsize / 2.0,
         );
 
-        let scale = ((self.scale_factor - 0.5) / 2.5 * 2.0 + 0.5) * fsize / 140.0;
+        let scale = ((self.parameters.scale_factor - 0.5) / 2.5 * 2.0 + 0.5) * fsize / 140.0;
+
+        (world_view, scale, shift)
+    }
+
+    /// The bones of the unicorn as they are traced from +world_view+, hair and tail split into
+    /// the segments that approximate their curves
+    pub(crate) fn bones(&self, world_view: &WorldView) -> Vec<(BallProjection, BallProjection)> {
+        let mut arena = TracerArena::new();
+
+        let group = self.unicorn.tracer(&mut arena, world_view);
+
+        let mut bones = Vec::new();
+        let mut pending: Vec<TracerId> = group.tracers().to_vec();
+
+        while let Some(id) = pending.pop() {
+            match arena.get(id) {
+                Tracer::BoneT(bone) => {
+                    let (b1, b2) = bone.balls();
+
+                    bones.push((b1.clone(), b2.clone()));
+                }
+                Tracer::GroupT(group) => pending.extend_from_slice(group.tracers()),
+                _ => (),
+            }
+        }
+
+        bones
+    }
+
+    /// The unicorn as a triangle mesh in world coordinates, a tapered capsule for every bone
+    /// the tracer draws
+    pub fn mesh(&self) -> Mesh {
+        // the bones are the same from any camera, only their projections differ
+        let (world_view, _, _) = self.camera(1);
+
+        let mut mesh = Mesh::new();
+
+        for (b1, b2) in self.bones(&world_view).iter() {
+            mesh.add_bone(&b1.base, &b2.base);
+        }
+
+        mesh
+    }
+
+    fn draw_options(&self, options: &RenderOptions, threads: usize) -> RgbaImage {
+        let size = options.size;
+        let quadrant = options.quadrant;
+        let region = options.region;
+        let with_background = options.background;
+        let shading = options.shading;
+        let grass = options.grass;
+
+        let (world_view, scale, shift) = self.camera(size);
 
         let image_size = match quadrant {
             Some(_) => size / 2,
@@ -138,35 +256,60 @@ impl Avatar {
         let mut image_buffer = RgbaImage::new(image_size, image_size);
 
         if with_background {
-            self.background.draw(&mut image_buffer, shading, quadrant);
+            self.parameters
+                .background
+                .draw(&mut image_buffer, shading, quadrant);
         }
 
-        let tracer = self.unicorn.tracer(&world_view);
+        let mut arena = TracerArena::new();
+
+        let tracer = self.unicorn.tracer(&mut arena, &world_view);
+        let tracer = arena.add(Tracer::GroupT(tracer));
 
         if shading {
             todo!("Implement shadow casting");
         }
 
-        let scaling = ScalingTracer::new(&world_view, Tracer::GroupT(tracer), scale);
-        let translating = TranslatingTracer::new(&world_view, Tracer::ScalingT(scaling), shift);
+        let scaling = ScalingTracer::new(&arena, &world_view, tracer, scale);
+        let scaling = arena.add(Tracer::ScalingT(scaling));
+        let translating = TranslatingTracer::new(&arena, &world_view, scaling, shift);
 
         if grass {
             todo!("Implement grass");
         }
 
-        let tracer = if parallelize {
-            todo!("Implement parallel tracing");
-        } else {
-            Tracer::TranslatingT(translating)
-        };
+        let tracer = arena.add(Tracer::TranslatingT(translating));
 
-        let mut tracer = match quadrant {
+        let tracer = match quadrant {
             None => tracer,
-            Some(q) => Tracer::QuadrantT(QuadrantTracer::new(&world_view, tracer, image_size, q)),
+            Some(q) => {
+                let quadrant = QuadrantTracer::new(&mut arena, &world_view, tracer, image_size, q);
+
+                arena.add(Tracer::QuadrantT(quadrant))
+            }
         };
 
-        tracer.draw(world_view, &mut image_buffer);
+        let mut window: Bounds = (&mut image_buffer).into();
+
+        if let Some(region) = region {
+            window.x_min = region.x as f64;
+            window.x_max = (region.x + region.width) as f64;
+            window.y_min = region.y as f64;
+            window.y_max = (region.y + region.height) as f64;
+        }
 
-        image_buffer
+        Tracer::draw_parallel(
+            &mut arena,
+            tracer,
+            world_view,
+            &mut image_buffer,
+            &window,
+            threads,
+        );
+
+        match region {
+            Some(r) => imageops::crop_imm(&image_buffer, r.x, r.y, r.width, r.height).to_image(),
+            None => image_buffer,
+        }
     }
 }
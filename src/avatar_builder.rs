@@ -0,0 +1,264 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::geometry::DEGREE;
+use crate::parameters::Parameters;
+use crate::unicorn::Pose;
+use crate::Avatar;
+use crate::GenerationProfile;
+use crate::Random;
+
+/// AvatarBuilder creates the Avatar for a hash with some of its traits pinned.
+///
+/// Every trait that is not pinned is drawn from the hash exactly like Avatar::new() does, so a
+/// customized unicorn stays recognizable as the same unicorn.
+///
+///     use unicornify::AvatarBuilder;
+///
+///     let avatar = AvatarBuilder::new("58479f76374a3ba3c69b9804163f39f4")
+///         .body_hue(200)
+///         .horn_length(100.0)
+///         .build()
+///         .unwrap();
+#[derive(Clone, Debug)]
+pub struct AvatarBuilder {
+    hash: String,
+    zoom_out: bool,
+    profile: GenerationProfile,
+    body_hue: Option<i32>,
+    horn_length: Option<f64>,
+    hair_count: Option<usize>,
+    pose: Option<Pose>,
+    x_angle: Option<f64>,
+    y_angle: Option<f64>,
+    sky_hue: Option<i32>,
+    rainbow_foot: Option<f64>,
+    rainbow_dir: Option<f64>,
+    rainbow_height: Option<f64>,
+    rainbow_band_width: Option<f64>,
+}
+
+impl AvatarBuilder {
+    pub fn new(hash: impl Into<String>) -> Self {
+        AvatarBuilder {
+            hash: hash.into(),
+            zoom_out: false,
+            profile: GenerationProfile::default(),
+            body_hue: None,
+            horn_length: None,
+            hair_count: None,
+            pose: None,
+            x_angle: None,
+            y_angle: None,
+            sky_hue: None,
+            rainbow_foot: None,
+            rainbow_dir: None,
+            rainbow_height: None,
+            rainbow_band_width: None,
+        }
+    }
+
+    pub fn zoom_out(mut self, zoom_out: bool) -> Self {
+        self.zoom_out = zoom_out;
+        self
+    }
+
+    /// Draws the traits that are not pinned from the ranges in +profile+ instead of the default
+    pub fn profile(mut self, profile: GenerationProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Pins the body hue (0 to 359).  The horn and hair keep their hue relative to the body.
+    pub fn body_hue(mut self, hue: i32) -> Self {
+        self.body_hue = Some(hue);
+        self
+    }
+
+    pub fn horn_length(mut self, length: f64) -> Self {
+        self.horn_length = Some(length);
+        self
+    }
+
+    /// Pins the number of hairs in the mane.  Fewer hairs keep the first ones, extra hairs are
+    /// drawn after all other traits so they do not change them.
+    pub fn hair_count(mut self, count: usize) -> Self {
+        self.hair_count = Some(count);
+        self
+    }
+
+    /// Pins the pose, including its phase
+    pub fn pose(mut self, pose: Pose) -> Self {
+        self.pose = Some(pose);
+        self
+    }
+
+    /// Pins the camera's angle above the unicorn in degrees
+    pub fn x_angle(mut self, degrees: f64) -> Self {
+        self.x_angle = Some(degrees);
+        self
+    }
+
+    /// Pins the camera's angle around the unicorn in degrees, 90 is a side view
+    pub fn y_angle(mut self, degrees: f64) -> Self {
+        self.y_angle = Some(degrees);
+        self
+    }
+
+    /// Pins the sky hue (0 to 359)
+    pub fn sky_hue(mut self, hue: i32) -> Self {
+        self.sky_hue = Some(hue);
+        self
+    }
+
+    /// Pins where the rainbow touches the ground, relative to the image width
+    pub fn rainbow_foot(mut self, foot: f64) -> Self {
+        self.rainbow_foot = Some(foot);
+        self
+    }
+
+    /// Pins the direction the rainbow arcs from its foot, 1.0 or -1.0
+    pub fn rainbow_dir(mut self, dir: f64) -> Self {
+        self.rainbow_dir = Some(dir);
+        self
+    }
+
+    /// Pins the rainbow's height relative to the image height
+    pub fn rainbow_height(mut self, height: f64) -> Self {
+        self.rainbow_height = Some(height);
+        self
+    }
+
+    /// Pins the width of each rainbow band relative to the image width
+    pub fn rainbow_band_width(mut self, width: f64) -> Self {
+        self.rainbow_band_width = Some(width);
+        self
+    }
+
+    pub fn build(&self) -> Result<Avatar> {
+        self.validate()?;
+
+        let mut rand = Random::new();
+
+        rand.seed_hex_string(self.hash.clone())
+            .context("Unable to use avatar hash")?;
+
+        let mut parameters = Parameters::generate(&mut rand, &self.profile, self.zoom_out);
+
+        let data = &mut parameters.data;
+
+        if let Some(hue) = self.body_hue {
+            data.horn_hue = (hue + (data.horn_hue - data.body_hue).rem_euclid(360)) % 360;
+            data.hair_hue = (hue + (data.hair_hue - data.body_hue).rem_euclid(360)) % 360;
+            data.body_hue = hue;
+        }
+
+        if let Some(length) = self.horn_length {
+            data.horn_length = length;
+        }
+
+        if let Some(count) = self.hair_count {
+            if count > data.hair_count {
+                let start = data.hair_count;
+                let extra = count - start;
+
+                data.make_hair1(&mut rand, &self.profile, start, extra);
+                data.make_hair2(&mut rand, &self.profile, start, extra);
+            } else {
+                data.hair_starts.truncate(count);
+                data.hair_gammas.truncate(count);
+                data.hair_lengths.truncate(count);
+                data.hair_angles.truncate(count);
+                data.hair_straightnesses.truncate(count);
+                data.hair_tip_lightnesses.truncate(count);
+            }
+
+            data.hair_count = count;
+        }
+
+        if let Some(pose) = &self.pose {
+            data.pose = pose.clone();
+        }
+
+        if let Some(x_angle) = self.x_angle {
+            data.x_angle = x_angle * DEGREE;
+        }
+
+        if let Some(y_angle) = self.y_angle {
+            data.y_angle = y_angle * DEGREE;
+        }
+
+        if self.x_angle.is_some() || self.y_angle.is_some() {
+            parameters.move_camera();
+        }
+
+        let background = &mut parameters.background;
+
+        if let Some(hue) = self.sky_hue {
+            background.sky_hue = hue;
+        }
+
+        if let Some(foot) = self.rainbow_foot {
+            background.rainbow_foot = foot;
+        }
+
+        if let Some(dir) = self.rainbow_dir {
+            background.rainbow_dir = dir;
+        }
+
+        if let Some(height) = self.rainbow_height {
+            background.rainbow_height = height;
+        }
+
+        if let Some(width) = self.rainbow_band_width {
+            background.rainbow_band_width = width;
+        }
+
+        // checks the assembled parameters like stored ones
+        Avatar::from_parameters(parameters)
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.profile.validate()?;
+
+        for (name, hue) in [("Body", self.body_hue), ("Sky", self.sky_hue)].iter() {
+            if let Some(hue) = hue {
+                if !(0..360).contains(hue) {
+                    bail!("{} hue must be between 0 and 359, not {}", name, hue);
+                }
+            }
+        }
+
+        let positive = [
+            ("Horn length", self.horn_length),
+            ("Rainbow foot", self.rainbow_foot),
+            ("Rainbow height", self.rainbow_height),
+            ("Rainbow band width", self.rainbow_band_width),
+        ];
+
+        for (name, value) in positive.iter() {
+            if let Some(value) = value {
+                if !(*value > 0.0 && value.is_finite()) {
+                    bail!("{} must be positive, not {}", name, value);
+                }
+            }
+        }
+
+        for (name, angle) in [("X angle", self.x_angle), ("Y angle", self.y_angle)].iter() {
+            if let Some(angle) = angle {
+                if !angle.is_finite() {
+                    bail!("{} must be finite, not {}", name, angle);
+                }
+            }
+        }
+
+        if let Some(dir) = self.rainbow_dir {
+            if dir != 1.0 && dir != -1.0 {
+                bail!("Rainbow direction must be 1 or -1, not {}", dir);
+            }
+        }
+
+        Ok(())
+    }
+}
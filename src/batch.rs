@@ -0,0 +1,304 @@
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::identity::email_hash;
+use crate::identity::parse_hash;
+use crate::Avatar;
+use crate::HashAlgorithm;
+use crate::OutputFormat;
+use crate::Provenance;
+use crate::RenderOptions;
+
+use std::any::Any;
+use std::fmt;
+use std::fs;
+use std::io::BufRead;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+
+/// Batch renders the avatars for many hashes or email addresses into a directory.
+///
+/// Each entry is rendered at every size in +sizes+ and written to +directory+ under a name built
+/// from +template+.  The template may use {hash}, {size} and {line}, and its extension picks the
+/// image format, for example "{size}/{hash}.png".
+///
+/// An entry that cannot be rendered is recorded in the BatchSummary and the rest of the batch
+/// continues.
+#[derive(Clone, Debug)]
+pub struct Batch {
+    pub sizes: Vec<u32>,
+    pub directory: PathBuf,
+    pub template: String,
+    /// Options for every image, the size is replaced by each of +sizes+
+    pub options: RenderOptions,
+    /// Hashes email address entries with this algorithm
+    pub algorithm: HashAlgorithm,
+    pub threads: usize,
+}
+
+/// An entry of a Batch that could not be rendered
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchFailure {
+    /// The line of the input the entry was on, starting at 1
+    pub line: usize,
+    pub input: String,
+    pub error: String,
+}
+
+/// What a Batch rendered
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BatchSummary {
+    pub entries: usize,
+    pub images: usize,
+    /// Failed entries in input order
+    pub failures: Vec<BatchFailure>,
+}
+
+// A hash or email address from the input
+struct Entry {
+    line: usize,
+    input: String,
+}
+
+impl Batch {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        let directory = directory.into();
+        let template = String::from("{hash}-{size}.png");
+
+        Batch {
+            sizes: vec![128],
+            directory,
+            template,
+            options: RenderOptions::default(),
+            algorithm: HashAlgorithm::Md5,
+            threads: 1,
+        }
+    }
+
+    /// Returns an error for a batch that cannot render or would write images over each other
+    pub fn validate(&self) -> Result<()> {
+        if self.sizes.is_empty() {
+            bail!("At least one size is needed to render a batch");
+        }
+
+        if self.threads == 0 {
+            bail!("At least one thread is needed to render a batch");
+        }
+
+        for size in self.sizes.iter() {
+            let mut options = self.options.clone();
+            options.size = *size;
+
+            options.validate()?;
+        }
+
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(e) => start + e,
+                None => bail!("Unclosed {{ in template {}", self.template),
+            };
+
+            match &rest[start + 1..end] {
+                "hash" | "size" | "line" => (),
+                p => bail!(
+                    "Unknown placeholder {{{}}} in template {}",
+                    p,
+                    self.template
+                ),
+            }
+
+            rest = &rest[end + 1..];
+        }
+
+        if !self.template.contains("{hash}") && !self.template.contains("{line}") {
+            bail!("Template {} needs {{hash}} or {{line}}", self.template);
+        }
+
+        if self.sizes.len() > 1 && !self.template.contains("{size}") {
+            bail!(
+                "Template {} needs {{size}} to render several sizes",
+                self.template
+            );
+        }
+
+        OutputFormat::from_path(&self.template)
+            .with_context(|| format!("Invalid template {}", self.template))?;
+
+        Ok(())
+    }
+
+    /// The file name the template gives the image of +hash+ at +size+ from input +line+
+    pub fn file_name(&self, hash: &str, size: u32, line: usize) -> String {
+        self.template
+            .replace("{hash}", hash)
+            .replace("{size}", &size.to_string())
+            .replace("{line}", &line.to_string())
+    }
+
+    /// Renders every hash or email address in +input+, one per line.  Blank lines and lines
+    /// starting with # are skipped.
+    ///
+    /// Returns an error only when the batch cannot start or +input+ cannot be read.
+    pub fn run(&self, input: impl BufRead) -> Result<BatchSummary> {
+        self.validate()?;
+
+        let mut entries = Vec::new();
+
+        for (i, line) in input.lines().enumerate() {
+            let line = line.context("Unable to read batch input")?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            entries.push(Entry {
+                line: i + 1,
+                input: line.to_string(),
+            });
+        }
+
+        fs::create_dir_all(&self.directory).with_context(|| {
+            format!(
+                "Unable to create output directory {}",
+                self.directory.display()
+            )
+        })?;
+
+        let next_entry = AtomicUsize::new(0);
+        let entries = &entries;
+
+        let results: Vec<(usize, Result<usize>)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads.min(entries.len().max(1)))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+
+                        loop {
+                            let i = next_entry.fetch_add(1, Ordering::Relaxed);
+
+                            let entry = match entries.get(i) {
+                                Some(e) => e,
+                                None => break,
+                            };
+
+                            let result =
+                                panic::catch_unwind(AssertUnwindSafe(|| self.render_entry(entry)))
+                                    .unwrap_or_else(|payload| Err(panic_error(payload)));
+
+                            results.push((i, result));
+                        }
+
+                        results
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_default())
+                .collect()
+        });
+
+        let mut results = results;
+        results.sort_by_key(|(i, _)| *i);
+
+        // entries of a worker that stopped without returning its results
+        for i in 0..entries.len() {
+            if results.get(i).map(|(r, _)| *r) != Some(i) {
+                results.insert(i, (i, Err(anyhow!("The worker rendering it stopped"))));
+            }
+        }
+
+        let mut summary = BatchSummary {
+            entries: entries.len(),
+            ..BatchSummary::default()
+        };
+
+        for (i, result) in results {
+            match result {
+                Ok(images) => summary.images += images,
+                Err(e) => summary.failures.push(BatchFailure {
+                    line: entries[i].line,
+                    input: entries[i].input.clone(),
+                    error: format!("{:#}", e),
+                }),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    // Renders +entry+ at every size, returning the number of images written
+    fn render_entry(&self, entry: &Entry) -> Result<usize> {
+        let hash = if entry.input.contains('@') {
+            email_hash(&entry.input, self.algorithm)
+        } else {
+            parse_hash(&entry.input)?
+        };
+
+        let avatar = Avatar::new(hash.clone(), self.options.zoom_out)?;
+
+        for size in self.sizes.iter() {
+            let mut options = self.options.clone();
+            options.size = *size;
+
+            let image = avatar.render(&options)?;
+            let path = self
+                .directory
+                .join(self.file_name(&hash, *size, entry.line));
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Unable to create {}", parent.display()))?;
+            }
+
+            let format = OutputFormat::from_path(&path)?;
+            let encoded = Provenance::new(&hash, &options).encode(format, &image)?;
+
+            fs::write(&path, encoded)
+                .with_context(|| format!("Unable to write {}", path.display()))?;
+        }
+
+        Ok(self.sizes.len())
+    }
+}
+
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} entries, {} images written, {} failed",
+            self.entries,
+            self.images,
+            self.failures.len()
+        )
+    }
+}
+
+impl fmt::Display for BatchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+// The error for an entry whose rendering panicked with +payload+
+pub(crate) fn panic_error(payload: Box<dyn Any + Send>) -> anyhow::Error {
+    let message = match payload.downcast_ref::<&str>() {
+        Some(m) => m.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(m) => m.clone(),
+            None => String::from("unknown panic"),
+        },
+    };
+
+    anyhow!("Rendering panicked: {}", message)
+}
@@ -1,22 +1,898 @@
-use image::DynamicImage;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
 
+use std::env;
 use std::env::args;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::Command;
+use std::process::ExitCode;
+use std::process::Stdio;
+use std::thread;
+use std::time::Duration;
 
+use unicornify::identity::email_hash;
+use unicornify::identity::parse_hash;
+use unicornify::terminal;
+use unicornify::Animation;
 use unicornify::Avatar;
+use unicornify::AvatarCache;
+use unicornify::Batch;
+use unicornify::HashAlgorithm;
+use unicornify::IconSet;
+use unicornify::OutputFormat;
+use unicornify::Provenance;
+use unicornify::Region;
+use unicornify::RenderOptions;
+use unicornify::Server;
 
-fn main() {
-    let hash = match args().nth(1) {
-        Some(h) => h,
-        None => String::from("58479f76374a3ba3c69b9804163f39f4"),
+const USAGE: &str = "usage: unicornify [OPTIONS] [HASH] [QUADRANT]
+       unicornify inspect [--json] [--zoom-out] [--email ADDRESS [--sha256]] [HASH]
+       unicornify batch [OPTIONS] [FILE]
+       unicornify animate [OPTIONS] [HASH]
+       unicornify export [OPTIONS] [HASH]
+       unicornify icons [OPTIONS] [HASH]
+       unicornify verify [--threads N] FILE...
+       unicornify serve [--listen ADDRESS] [--max-size PIXELS] [--cache-dir DIR]
+       unicornify --help";
+
+const HELP: &str = "Renders the unicorn avatar for an MD5 or SHA-256 hash, or for an email address.
+
+inspect prints the parameters generated for the avatar.  batch renders every hash or email
+address in FILE, or standard input, one per line.  serve answers GET /avatar/HASH?s=SIZE
+requests over HTTP.  animate writes a GIF of one cycle of the unicorn's gait, or
+of the camera circling it with --turntable.  export writes the 3D model of the unicorn as a
+binary glTF file, or as a POV-Ray scene seen by the camera of the image.  icons renders the
+avatar at every icon size from 16 to 512 pixels into a directory of PNGs, a favicon.ico with
+the sizes up to 256 and a manifest.json listing them.
+
+PNG images record the hash, size and options they were rendered with.  verify reads them back
+from each FILE, renders the avatar again and checks the pixels match.
+
+Options:
+  -s, --size PIXELS       width and height of the image (default 128), batch and icons accept
+                          several
+  -o, --output PATH       where to write the image, - for standard output (default out.png,
+                          or out with the extension of --format, out.gif or out.y4m for
+                          animate, out.glb for export).  animate writes each frame to its
+                          own image when PATH contains {frame}
+  -f, --format FORMAT     png, jpeg, bmp, gif, ico, webp (lossless), rgba (raw pixels, written
+                          to standard output unless -o is given) or svg (default from the
+                          output extension, else png), ansi or sixel to preview in the terminal,
+                          animate writes gif or y4m video, export glb or pov
+      --quality N         JPEG quality from 1 to 100 (default 90)
+      --no-background     draw the unicorn on a transparent background
+      --zoom-out          show the whole unicorn
+      --shading           cast shadows (not implemented yet)
+      --grass             draw grass (not implemented yet)
+  -j, --threads N         render on N threads (default all processors), serve answers at most
+                          N requests at once
+  -q, --quadrant N        render only quadrant N (1 to 4) of the image
+      --region X,Y,W,H    render only this rectangle of the image
+      --email ADDRESS     render the avatar for an email address
+      --md5, --sha256     hash the email address with this algorithm (default md5)
+      --json              print inspect output as JSON
+      --out-dir DIR       directory batch writes images to (default .), or icons (default
+                          icons)
+      --template NAME     batch file names from {hash}, {size} and {line}, the extension picks
+                          the format (default {hash}-{size}.png)
+      --frames N          frames in an animation (default 24)
+      --turntable         animate the camera circling the unicorn instead of its gait
+      --delay MS          milliseconds each animation frame is shown (default 40)
+      --listen ADDRESS    address serve listens on (default 127.0.0.1:8080)
+      --max-size PIXELS   largest image serve renders (default 512)
+      --cache-dir DIR     directory serve keeps rendered images in
+  -h, --help              print this help
+
+Exit status is 0 on success, 1 when rendering fails and 2 for invalid arguments.";
+
+const DEFAULT_HASH: &str = "58479f76374a3ba3c69b9804163f39f4";
+
+// The options each command uses, --help is accepted by all of them
+const RENDER_FLAGS: &[&str] = &[
+    "--email",
+    "--md5",
+    "--sha256",
+    "--size",
+    "--output",
+    "--format",
+    "--quality",
+    "--no-background",
+    "--zoom-out",
+    "--shading",
+    "--grass",
+    "--threads",
+    "--quadrant",
+    "--region",
+];
+const INSPECT_FLAGS: &[&str] = &["--email", "--md5", "--sha256", "--json", "--zoom-out"];
+const BATCH_FLAGS: &[&str] = &[
+    "--email",
+    "--md5",
+    "--sha256",
+    "--size",
+    "--no-background",
+    "--zoom-out",
+    "--shading",
+    "--grass",
+    "--threads",
+    "--quadrant",
+    "--region",
+    "--out-dir",
+    "--template",
+];
+const ANIMATE_FLAGS: &[&str] = &[
+    "--email",
+    "--md5",
+    "--sha256",
+    "--size",
+    "--output",
+    "--format",
+    "--no-background",
+    "--zoom-out",
+    "--shading",
+    "--grass",
+    "--threads",
+    "--quadrant",
+    "--region",
+    "--frames",
+    "--turntable",
+    "--delay",
+];
+const EXPORT_FLAGS: &[&str] = &[
+    "--email",
+    "--md5",
+    "--sha256",
+    "--size",
+    "--output",
+    "--format",
+    "--no-background",
+    "--zoom-out",
+    "--quadrant",
+    "--region",
+];
+// the glb mesh has no camera, so the image options only apply to pov
+const GLB_EXPORT_FLAGS: &[&str] = &[
+    "--email",
+    "--md5",
+    "--sha256",
+    "--output",
+    "--format",
+    "--zoom-out",
+];
+const ICONS_FLAGS: &[&str] = &[
+    "--email",
+    "--md5",
+    "--sha256",
+    "--size",
+    "--no-background",
+    "--zoom-out",
+    "--shading",
+    "--grass",
+    "--threads",
+    "--out-dir",
+];
+const VERIFY_FLAGS: &[&str] = &["--threads"];
+const SERVE_FLAGS: &[&str] = &["--threads", "--listen", "--max-size", "--cache-dir"];
+
+// Errors in the command line, reported with the usage
+#[derive(Debug)]
+struct UsageError(String);
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\n{}", self.0, USAGE)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+macro_rules! usage {
+    ($($arg:tt)*) => {
+        return Err(UsageError(format!($($arg)*)).into())
+    };
+}
+
+struct Args {
+    help: bool,
+    email: Option<String>,
+    algorithm: HashAlgorithm,
+    json: bool,
+    options: RenderOptions,
+    output: Option<String>,
+    format: Option<String>,
+    quality: Option<u8>,
+    threads: Option<usize>,
+    sizes: Vec<u32>,
+    out_dir: Option<String>,
+    template: Option<String>,
+    frames: usize,
+    turntable: bool,
+    delay: u64,
+    listen: String,
+    max_size: u32,
+    cache_dir: Option<String>,
+    positional: Vec<String>,
+    // the long names of the options given, in order
+    given: Vec<&'static str>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut parsed = Args {
+            help: false,
+            email: None,
+            algorithm: HashAlgorithm::Md5,
+            json: false,
+            options: RenderOptions::default(),
+            output: None,
+            format: None,
+            quality: None,
+            threads: None,
+            sizes: Vec::new(),
+            out_dir: None,
+            template: None,
+            frames: 24,
+            turntable: false,
+            delay: 40,
+            listen: String::from("127.0.0.1:8080"),
+            max_size: 512,
+            cache_dir: None,
+            positional: Vec::new(),
+            given: Vec::new(),
+        };
+
+        let mut args = args;
+
+        while let Some(arg) = args.next() {
+            // --option=value is the same as --option value
+            let (arg, mut inline) = match arg.split_once('=') {
+                Some((a, v)) if a.starts_with("--") => (a.to_string(), Some(v.to_string())),
+                _ => (arg, None),
+            };
+
+            let mut value = |name: &str| match inline.take().or_else(|| args.next()) {
+                Some(v) => Ok(v),
+                None => Err(UsageError(format!("{} requires a value", name))),
+            };
+
+            let name = match arg.as_str() {
+                "-h" | "--help" => "--help",
+                "--email" => "--email",
+                "--md5" => "--md5",
+                "--sha256" => "--sha256",
+                "--json" => "--json",
+                "--turntable" => "--turntable",
+                "--zoom-out" => "--zoom-out",
+                "--no-background" => "--no-background",
+                "--shading" => "--shading",
+                "--grass" => "--grass",
+                "-s" | "--size" => "--size",
+                "-o" | "--output" => "--output",
+                "-f" | "--format" => "--format",
+                "--quality" => "--quality",
+                "--out-dir" => "--out-dir",
+                "--template" => "--template",
+                "--frames" => "--frames",
+                "--delay" => "--delay",
+                "--listen" => "--listen",
+                "--max-size" => "--max-size",
+                "--cache-dir" => "--cache-dir",
+                "-j" | "--threads" => "--threads",
+                "-q" | "--quadrant" => "--quadrant",
+                "--region" => "--region",
+                a if a.starts_with('-') && a.len() > 1 => usage!("Unknown option {}", a),
+                _ => "",
+            };
+
+            if !name.is_empty() {
+                parsed.given.push(name);
+            }
+
+            match arg.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "--email" => parsed.email = Some(value("--email")?),
+                "--md5" => parsed.algorithm = HashAlgorithm::Md5,
+                "--sha256" => parsed.algorithm = HashAlgorithm::Sha256,
+                "--json" => parsed.json = true,
+                "--turntable" => parsed.turntable = true,
+                "--zoom-out" => parsed.options.zoom_out = true,
+                "--no-background" => parsed.options.background = false,
+                "--shading" => parsed.options.shading = true,
+                "--grass" => parsed.options.grass = true,
+                "-s" | "--size" => {
+                    parsed.options.size = number("--size", &value("--size")?)?;
+                    parsed.sizes.push(parsed.options.size);
+                }
+                "-o" | "--output" => parsed.output = Some(value("--output")?),
+                "-f" | "--format" => parsed.format = Some(value("--format")?),
+                "--quality" => {
+                    let quality = number("--quality", &value("--quality")?)?;
+
+                    if !(1..=100).contains(&quality) {
+                        usage!("--quality must be 1 to 100");
+                    }
+
+                    parsed.quality = Some(quality);
+                }
+                "--out-dir" => parsed.out_dir = Some(value("--out-dir")?),
+                "--template" => parsed.template = Some(value("--template")?),
+                "--frames" => parsed.frames = number("--frames", &value("--frames")?)?,
+                "--delay" => parsed.delay = number("--delay", &value("--delay")?)?,
+                "--listen" => parsed.listen = value("--listen")?,
+                "--max-size" => parsed.max_size = number("--max-size", &value("--max-size")?)?,
+                "--cache-dir" => parsed.cache_dir = Some(value("--cache-dir")?),
+                "-j" | "--threads" => {
+                    parsed.threads = Some(number("--threads", &value("--threads")?)?)
+                }
+                "-q" | "--quadrant" => {
+                    parsed.options.quadrant = Some(number("--quadrant", &value("--quadrant")?)?)
+                }
+                "--region" => {
+                    let region = value("--region")?;
+
+                    match region.parse::<Region>() {
+                        Ok(r) => parsed.options.region = Some(r),
+                        Err(e) => usage!("{}", e),
+                    }
+                }
+                _ => parsed.positional.push(arg.clone()),
+            }
+
+            if inline.is_some() {
+                usage!("{} does not take a value", arg);
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    // Rejects options +command+ does not use, it uses only +accepted+
+    fn check_flags(&self, command: &str, accepted: &[&str]) -> Result<()> {
+        for flag in self.given.iter() {
+            if *flag != "--help" && !accepted.contains(flag) {
+                usage!("{} cannot be used with {}", flag, command);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn threads(&self) -> Result<usize> {
+        match self.threads {
+            Some(0) => usage!("--threads must be at least 1"),
+            Some(n) => Ok(n),
+            None => Ok(thread::available_parallelism().map_or(1, |n| n.get())),
+        }
+    }
+
+    // Takes the hash from --email or the first positional argument
+    fn hash(&mut self) -> Result<String> {
+        if let Some(e) = &self.email {
+            return Ok(email_hash(e, self.algorithm));
+        }
+
+        if self.positional.is_empty() {
+            Ok(String::from(DEFAULT_HASH))
+        } else {
+            parse_hash(&self.positional.remove(0))
+                .map_err(|e| UsageError(format!("{:#}", e)).into())
+        }
+    }
+}
+
+fn number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, UsageError> {
+    value
+        .parse()
+        .map_err(|_| UsageError(format!("Invalid {} {}", name, value)))
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("unicornify: error: {:#}", e);
+
+            if e.is::<UsageError>() {
+                ExitCode::from(2)
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
+
+fn run() -> Result<()> {
+    let mut args = args().skip(1).peekable();
+
+    let command = match args.peek().map(String::as_str) {
+        Some("inspect") | Some("batch") | Some("serve") | Some("animate") | Some("export")
+        | Some("icons") | Some("verify") => args.next(),
+        _ => None,
+    };
+
+    let args = Args::parse(args)?;
+
+    if args.help {
+        println!("{}\n\n{}", USAGE, HELP);
+
+        return Ok(());
+    }
+
+    let accepted = match command.as_deref() {
+        Some("inspect") => INSPECT_FLAGS,
+        Some("batch") => BATCH_FLAGS,
+        Some("serve") => SERVE_FLAGS,
+        Some("animate") => ANIMATE_FLAGS,
+        Some("export") => EXPORT_FLAGS,
+        Some("icons") => ICONS_FLAGS,
+        Some("verify") => VERIFY_FLAGS,
+        _ => RENDER_FLAGS,
+    };
+
+    args.check_flags(command.as_deref().unwrap_or("rendering"), accepted)?;
+
+    match command.as_deref() {
+        Some("inspect") => inspect(args),
+        Some("batch") => batch(args),
+        Some("serve") => serve(args),
+        Some("animate") => animate(args),
+        Some("export") => export(args),
+        Some("icons") => icons(args),
+        Some("verify") => verify(args),
+        _ => render(args),
+    }
+}
+
+fn batch(args: Args) -> Result<()> {
+    if args.positional.len() > 1 {
+        usage!("Unexpected argument {}", args.positional[1]);
+    }
+
+    if args.email.is_some() {
+        usage!("--email cannot be used with batch, list addresses in the input instead");
+    }
+
+    let mut batch = Batch::new(args.out_dir.as_deref().unwrap_or("."));
+
+    if !args.sizes.is_empty() {
+        batch.sizes = args.sizes;
+    }
+
+    if let Some(template) = args.template {
+        batch.template = template;
+    }
+
+    batch.options = args.options;
+    batch.algorithm = args.algorithm;
+    batch.threads = match args.threads {
+        Some(n) => n,
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
     };
-    let quadrant = match args().nth(2) {
-        Some(q) => Some(q.parse::<u8>().unwrap()),
-        None => None,
+
+    if let Err(e) = batch.validate() {
+        usage!("{:#}", e);
+    }
+
+    let summary = match args.positional.first().map(String::as_str) {
+        None | Some("-") => batch.run(io::stdin().lock())?,
+        Some(path) => {
+            let file = File::open(path).with_context(|| format!("Unable to open {}", path))?;
+
+            batch.run(BufReader::new(file))?
+        }
     };
 
-    let avatar = Avatar::new(hash, false).unwrap();
-    let image_buffer = avatar.draw(128, quadrant, true, false, false, false, false);
+    for failure in summary.failures.iter() {
+        eprintln!("unicornify: {}", failure);
+    }
+
+    println!("{}", summary);
+
+    if !summary.failures.is_empty() {
+        bail!(
+            "{} of {} entries failed",
+            summary.failures.len(),
+            summary.entries
+        );
+    }
+
+    Ok(())
+}
+
+fn serve(args: Args) -> Result<()> {
+    if let Some(extra) = args.positional.first() {
+        usage!("Unexpected argument {}", extra);
+    }
+
+    if args.max_size == 0 {
+        usage!("--max-size must be at least 1");
+    }
+
+    let cache = match &args.cache_dir {
+        Some(dir) => AvatarCache::with_directory(1024, dir)?,
+        None => AvatarCache::new(1024),
+    };
+
+    let listener = TcpListener::bind(&args.listen)
+        .with_context(|| format!("Unable to listen on {}", args.listen))?;
+
+    eprintln!(
+        "unicornify: serving avatars on http://{}/avatar/",
+        args.listen
+    );
+
+    Server::new(cache, args.max_size)
+        .with_workers(args.threads()?)
+        .serve(listener)
+}
+
+fn inspect(mut args: Args) -> Result<()> {
+    let hash = args.hash()?;
+
+    if let Some(extra) = args.positional.first() {
+        usage!("Unexpected argument {}", extra);
+    }
+
+    let avatar = Avatar::new(hash.clone(), args.options.zoom_out)?;
+
+    if args.json {
+        println!("{}", avatar.parameters().to_json()?);
+    } else {
+        println!("hash {}", hash);
+        println!("{}", avatar.parameters());
+    }
+
+    Ok(())
+}
+
+fn render(mut args: Args) -> Result<()> {
+    let hash = args.hash()?;
+
+    // the quadrant was positional before --quadrant existed
+    if !args.positional.is_empty() {
+        let q = args.positional.remove(0);
+
+        if args.options.quadrant.is_some() {
+            usage!("Quadrant given twice");
+        }
+
+        args.options.quadrant = Some(number("quadrant", &q)?);
+    }
+
+    if let Some(extra) = args.positional.first() {
+        usage!("Unexpected argument {}", extra);
+    }
+
+    if let Err(e) = args.options.validate() {
+        usage!("{:#}", e);
+    }
+
+    let format = args.format.as_deref().map(str::to_ascii_lowercase);
+
+    if let Some(preview @ ("ansi" | "sixel")) = format.as_deref() {
+        // one pixel per character cell, sixel cells are at least eight pixels wide
+        if args.sizes.is_empty() {
+            let columns = terminal_columns();
+
+            args.options.size = match preview {
+                "ansi" => columns,
+                _ => (columns * 8).min(512),
+            };
+
+            if let Err(e) = args.options.validate() {
+                usage!("{:#}", e);
+            }
+        }
+
+        let output = args.output.clone().unwrap_or_else(|| String::from("-"));
+
+        let avatar = Avatar::new(hash, args.options.zoom_out)?;
+        let image = avatar.render_threaded(&args.options, args.threads()?)?;
+
+        let text = match preview {
+            "ansi" => terminal::half_blocks(&image),
+            _ => terminal::sixel(&image),
+        };
+
+        return write_output(&output, |writer| Ok(writer.write_all(text.as_bytes())?));
+    }
+
+    // raw pixels have no header so they are meant for a pipe, other formats name the file
+    let output = match (&args.output, args.format.as_deref()) {
+        (Some(o), _) => o.clone(),
+        (None, Some(f)) if f.eq_ignore_ascii_case("svg") => String::from("out.svg"),
+        (None, Some(f)) => match f.parse::<OutputFormat>() {
+            Ok(OutputFormat::Rgba) => String::from("-"),
+            Ok(format) => format!("out.{}", format.extension()),
+            // reported by output_format()
+            Err(_) => String::from("out.png"),
+        },
+        (None, None) => String::from("out.png"),
+    };
+
+    let svg = match args.format.as_deref() {
+        Some(f) => f.eq_ignore_ascii_case("svg"),
+        None => output.to_ascii_lowercase().ends_with(".svg"),
+    };
+
+    if svg {
+        let avatar = Avatar::new(hash, args.options.zoom_out)?;
+        let svg = avatar.render_svg(&args.options)?;
+
+        return write_output(&output, |writer| Ok(writer.write_all(svg.as_bytes())?));
+    }
+
+    let format = output_format(&output, args.format.as_deref(), args.quality)?;
+    let threads = args.threads()?;
+
+    let avatar = Avatar::new(hash.clone(), args.options.zoom_out)?;
+    let image = avatar.render_threaded(&args.options, threads)?;
+    let encoded = Provenance::new(&hash, &args.options).encode(format, &image)?;
+
+    write_output(&output, |writer| Ok(writer.write_all(&encoded)?))
+}
+
+fn animate(mut args: Args) -> Result<()> {
+    let hash = args.hash()?;
+
+    if let Some(extra) = args.positional.first() {
+        usage!("Unexpected argument {}", extra);
+    }
+
+    if let Err(e) = args.options.validate() {
+        usage!("{:#}", e);
+    }
+
+    if args.frames == 0 {
+        usage!("--frames must be at least 1");
+    }
+
+    let output = match (&args.output, args.format.as_deref()) {
+        (Some(o), _) => o.clone(),
+        (None, Some(f)) if f.eq_ignore_ascii_case("y4m") => String::from("out.y4m"),
+        (None, _) => String::from("out.gif"),
+    };
+
+    // each frame is written to an image in the format of the template's extension
+    let frames = output.contains("{frame}");
+
+    if frames {
+        if args.format.is_some() {
+            usage!("--format cannot be used when {{frame}} writes each frame to an image");
+        }
+
+        if let Err(e) = OutputFormat::from_path(&output) {
+            usage!("{:#}", e);
+        }
+    }
+
+    let y4m = match args.format.as_deref() {
+        _ if frames => false,
+        Some(f) if f.eq_ignore_ascii_case("y4m") => true,
+        Some(f) if f.eq_ignore_ascii_case("gif") => false,
+        Some(f) => usage!("Unknown animation format {}, use gif or y4m", f),
+        None => match Path::new(&output).extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("y4m") => true,
+            Some(e) if e.eq_ignore_ascii_case("gif") => false,
+            Some(_) => usage!(
+                "{} is not a gif or y4m file, use --format to pick one",
+                output
+            ),
+            None => false,
+        },
+    };
+
+    let threads = args.threads()?;
+
+    let avatar = Avatar::new(hash, args.options.zoom_out)?;
+
+    let mut animation = if args.turntable {
+        Animation::turntable(&avatar, &args.options, args.frames, threads)?
+    } else {
+        Animation::gait(&avatar, &args.options, args.frames, threads)?
+    };
+
+    animation.frame_delay = Duration::from_millis(args.delay);
+
+    if frames {
+        animation.save_frames(&output)?;
+
+        return Ok(());
+    }
+
+    if y4m {
+        write_output(&output, |writer| animation.write_y4m(writer))
+    } else {
+        write_output(&output, |writer| animation.write_gif(writer))
+    }
+}
+
+fn export(mut args: Args) -> Result<()> {
+    let hash = args.hash()?;
+
+    if let Some(extra) = args.positional.first() {
+        usage!("Unexpected argument {}", extra);
+    }
+
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| String::from("out.glb"));
+
+    let pov = match args.format.as_deref() {
+        Some(f) if f.eq_ignore_ascii_case("pov") => true,
+        Some(f) if f.eq_ignore_ascii_case("glb") => false,
+        Some(f) => usage!("Unknown export format {}, use glb or pov", f),
+        None => output.to_ascii_lowercase().ends_with(".pov"),
+    };
+
+    if !pov {
+        args.check_flags("glb export", GLB_EXPORT_FLAGS)?;
+    }
+
+    let avatar = Avatar::new(hash, args.options.zoom_out)?;
+
+    if pov {
+        if let Err(e) = args.options.validate() {
+            usage!("{:#}", e);
+        }
+
+        let scene = avatar.render_pov(&args.options)?;
+
+        return write_output(&output, |writer| Ok(writer.write_all(scene.as_bytes())?));
+    }
+
+    let mesh = avatar.mesh();
+
+    write_output(&output, |writer| mesh.write_glb(writer))
+}
+
+fn icons(mut args: Args) -> Result<()> {
+    let hash = args.hash()?;
+
+    if let Some(extra) = args.positional.first() {
+        usage!("Unexpected argument {}", extra);
+    }
+
+    let mut icons = IconSet::new(args.out_dir.as_deref().unwrap_or("icons"));
+
+    if !args.sizes.is_empty() {
+        icons.sizes = args.sizes.clone();
+    }
+
+    icons.threads = args.threads()?;
+    icons.options = args.options;
+
+    if let Err(e) = icons.validate() {
+        usage!("{:#}", e);
+    }
+
+    let avatar = Avatar::new(hash, icons.options.zoom_out)?;
+
+    for path in icons.write(&avatar)? {
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
+
+fn verify(args: Args) -> Result<()> {
+    if args.positional.is_empty() {
+        usage!("verify needs at least one PNG file");
+    }
+
+    let threads = args.threads()?;
+
+    let mut failed = 0;
+
+    for path in args.positional.iter() {
+        let result = fs::read(path)
+            .with_context(|| format!("Unable to read {}", path))
+            .and_then(|png| {
+                let provenance = Provenance::read(&png)?;
+
+                println!("{}: {}", path, provenance);
+
+                provenance.verify(&png, threads)
+            });
+
+        match result {
+            Ok(0) => println!("{}: verified", path),
+            Ok(differ) => {
+                println!("{}: {} pixels differ", path, differ);
+
+                failed += 1;
+            }
+            Err(e) => {
+                eprintln!("unicornify: {}: {:#}", path, e);
+
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!(
+            "{} of {} images failed verification",
+            failed,
+            args.positional.len()
+        );
+    }
+
+    Ok(())
+}
+
+// The width of the terminal in characters from $COLUMNS or stty, otherwise 80
+fn terminal_columns() -> u32 {
+    if let Some(columns) = env::var("COLUMNS").ok().and_then(|c| c.parse().ok()) {
+        return columns;
+    }
+
+    let stty = File::open("/dev/tty").and_then(|tty| {
+        Command::new("stty")
+            .arg("size")
+            .stdin(tty)
+            .stderr(Stdio::null())
+            .output()
+    });
+
+    // stty size prints rows and columns
+    stty.ok()
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .and_then(|size| size.split_whitespace().nth(1).and_then(|c| c.parse().ok()))
+        .filter(|&c| c > 0)
+        .unwrap_or(80)
+}
+
+// Writes to standard output for -, otherwise to the file +output+
+fn write_output(output: &str, write: impl FnOnce(&mut dyn Write) -> Result<()>) -> Result<()> {
+    if output == "-" {
+        let mut encoded = Vec::new();
+
+        write(&mut encoded).context("Unable to encode image")?;
+
+        io::stdout()
+            .write_all(&encoded)
+            .context("Unable to write image to standard output")?;
+    } else {
+        let file = File::create(output).with_context(|| format!("Unable to create {}", output))?;
+        let mut writer = BufWriter::new(file);
+
+        write(&mut writer).with_context(|| format!("Unable to write {}", output))?;
+
+        writer
+            .flush()
+            .with_context(|| format!("Unable to write {}", output))?;
+    }
+
+    Ok(())
+}
+
+// Uses +format+ if given, otherwise the extension of +output+, otherwise PNG.  JPEG is encoded
+// at +quality+ if given.
+fn output_format(output: &str, format: Option<&str>, quality: Option<u8>) -> Result<OutputFormat> {
+    let format = match format {
+        Some(f) => f.parse(),
+        None if Path::new(output).extension().is_none() => Ok(OutputFormat::Png),
+        None => OutputFormat::from_path(output),
+    };
+
+    let format = match format {
+        Ok(f) => f,
+        Err(e) => usage!("{:#}", e),
+    };
 
-    let image = DynamicImage::ImageRgba8(image_buffer);
-    image.save("out.png").unwrap();
+    match (format, quality) {
+        (OutputFormat::Jpeg(_), Some(q)) => Ok(OutputFormat::Jpeg(q)),
+        (_, Some(_)) => usage!("--quality only applies to JPEG"),
+        (f, None) => Ok(f),
+    }
 }
@@ -0,0 +1,239 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::Avatar;
+use crate::Provenance;
+use crate::RenderOptions;
+use crate::RENDER_VERSION;
+
+use std::collections::HashMap;
+use std::fs;
+use std::hash::Hash;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+type AvatarKey = (String, bool);
+type ImageKey = (String, RenderOptions);
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// Every complete PNG ends with an empty IEND chunk and its CRC
+const PNG_END: &[u8] = b"\0\0\0\0IEND\xae\x42\x60\x82";
+
+// Tells apart the temporary files of threads writing the same cached image
+static TEMPORARY: AtomicUsize = AtomicUsize::new(0);
+
+/// AvatarCache is a thread-safe cache of generated Avatars and their PNG encoded images.
+///
+/// Both are kept in memory in least-recently-used order up to +capacity+ entries each.  When a
+/// cache directory is given encoded images are also written there and read back on a miss, so they
+/// survive restarts.
+///
+/// Hashes are not case sensitive.  Two threads that miss on the same key at the same time will
+/// both render it.  Images are renamed into the directory once fully written, and a truncated
+/// file, say from a crash, is rendered again.
+pub struct AvatarCache {
+    avatars: Mutex<Lru<AvatarKey, Arc<Avatar>>>,
+    images: Mutex<Lru<ImageKey, Arc<Vec<u8>>>>,
+    directory: Option<PathBuf>,
+}
+
+impl AvatarCache {
+    pub fn new(capacity: usize) -> Self {
+        let avatars = Mutex::new(Lru::new(capacity));
+        let images = Mutex::new(Lru::new(capacity));
+        let directory = None;
+
+        AvatarCache {
+            avatars,
+            images,
+            directory,
+        }
+    }
+
+    /// Creates a cache that also persists encoded images in +directory+, creating it if needed
+    pub fn with_directory(capacity: usize, directory: impl Into<PathBuf>) -> Result<Self> {
+        let directory = directory.into();
+
+        fs::create_dir_all(&directory)
+            .with_context(|| format!("Unable to create cache directory {}", directory.display()))?;
+
+        let mut cache = AvatarCache::new(capacity);
+        cache.directory = Some(directory);
+
+        Ok(cache)
+    }
+
+    /// Returns the Avatar for +hash+, generating it on a miss
+    pub fn avatar(&self, hash: &str, zoom_out: bool) -> Result<Arc<Avatar>> {
+        let hash = hash.to_ascii_lowercase();
+        let key = (hash.clone(), zoom_out);
+
+        if let Some(avatar) = self.avatars.lock().unwrap().get(&key) {
+            return Ok(avatar);
+        }
+
+        let avatar = Arc::new(Avatar::new(hash, zoom_out)?);
+
+        self.avatars.lock().unwrap().insert(key, avatar.clone());
+
+        Ok(avatar)
+    }
+
+    /// Returns the PNG encoded image for +hash+ drawn with +options+, rendering it on a miss
+    pub fn image(&self, hash: &str, options: &RenderOptions) -> Result<Arc<Vec<u8>>> {
+        if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("Invalid hex string {}", hash);
+        }
+
+        let hash = hash.to_ascii_lowercase();
+        let hash = hash.as_str();
+
+        let key = (hash.to_string(), options.clone());
+
+        if let Some(image) = self.images.lock().unwrap().get(&key) {
+            return Ok(image);
+        }
+
+        let path = self.image_path(hash, options);
+
+        let image = match path.as_deref().and_then(read_cached) {
+            Some(png) => Arc::new(png),
+            None => {
+                let png = Arc::new(self.render(hash, options)?);
+
+                if let Some(path) = path {
+                    write_cached(&path, &png)?;
+                }
+
+                png
+            }
+        };
+
+        self.images.lock().unwrap().insert(key, image.clone());
+
+        Ok(image)
+    }
+
+    // The render and crate versions in the name keep images drawn, and provenance recorded, by
+    // other builds from being served
+    fn image_path(&self, hash: &str, options: &RenderOptions) -> Option<PathBuf> {
+        let flag = |f, c| if f { c } else { '-' };
+
+        let region = match options.region {
+            Some(r) => format!("-{}_{}_{}_{}", r.x, r.y, r.width, r.height),
+            None => String::new(),
+        };
+
+        let name = format!(
+            "v{}-{}-{}-{}-{}-{}{}{}{}{}.png",
+            RENDER_VERSION,
+            env!("CARGO_PKG_VERSION"),
+            hash,
+            options.size,
+            options.quadrant.unwrap_or(0),
+            flag(options.background, 'b'),
+            flag(options.zoom_out, 'z'),
+            flag(options.shading, 's'),
+            flag(options.grass, 'g'),
+            region,
+        );
+
+        self.directory.as_ref().map(|d| d.join(name))
+    }
+
+    fn render(&self, hash: &str, options: &RenderOptions) -> Result<Vec<u8>> {
+        let avatar = self.avatar(hash, options.zoom_out)?;
+        let image = avatar.render(options)?;
+
+        Provenance::new(hash, options)
+            .encode_png(&image)
+            .with_context(|| format!("Unable to encode avatar {}", hash))
+    }
+}
+
+// Reads the cached PNG at +path+, a missing, unreadable or truncated file is a miss
+fn read_cached(path: &Path) -> Option<Vec<u8>> {
+    let png = fs::read(path).ok()?;
+
+    if png.starts_with(PNG_SIGNATURE) && png.ends_with(PNG_END) {
+        Some(png)
+    } else {
+        None
+    }
+}
+
+// Writes +png+ beside +path+ and renames it into place, so a reader never sees part of an image
+fn write_cached(path: &Path, png: &[u8]) -> Result<()> {
+    let temporary = path.with_extension(format!(
+        "{}-{}.tmp",
+        process::id(),
+        TEMPORARY.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = fs::write(&temporary, png).and_then(|_| fs::rename(&temporary, path));
+
+    if written.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+
+    written.with_context(|| format!("Unable to write cached image {}", path.display()))
+}
+
+// A least-recently-used map holding at most +capacity+ entries.  Eviction scans every entry which
+// is cheap next to rendering an avatar.
+struct Lru<K, V> {
+    capacity: usize,
+    clock: u64,
+    entries: HashMap<K, (u64, V)>,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Lru<K, V> {
+    fn new(capacity: usize) -> Self {
+        let clock = 0;
+        let entries = HashMap::with_capacity(capacity);
+
+        Lru {
+            capacity,
+            clock,
+            entries,
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.clock += 1;
+        let clock = self.clock;
+
+        self.entries.get_mut(key).map(|(used, value)| {
+            *used = clock;
+            value.clone()
+        })
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(k, _)| k.clone());
+
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.entries.insert(key, (self.clock, value));
+    }
+}
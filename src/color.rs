@@ -3,6 +3,7 @@ use image::Rgba;
 use std::convert::Into;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Color {
     pub r: u8,
     pub g: u8,
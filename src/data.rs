@@ -1,9 +1,11 @@
-use crate::Random;
+use crate::GenerationProfile;
+use crate::Rng;
 
 use crate::geometry::DEGREE;
 use crate::unicorn::Pose;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Data {
     pub head_size: f64,
     pub snout_size: f64,
@@ -99,29 +101,29 @@ impl Data {
         }
     }
 
-    pub fn rand1(&mut self, rand: &mut Random) {
-        self.body_hue = rand.rand_i32(0, 359);
-        self.body_sat = rand.rand_i32(50, 100);
-        self.horn_hue = (self.body_hue + rand.rand_i32(60, 300)) % 360;
-        self.horn_sat = rand.rand_i32(50, 100);
-        self.snout_size = rand.rand_i32(8, 30) as f64;
-        self.snout_length = rand.rand_i32(70, 110) as f64;
-        self.head_size = rand.rand_i32(25, 40) as f64;
-        self.shoulder_size = rand.rand_i32(40, 60) as f64;
-        self.butt_size = rand.rand_i32(30, 60) as f64;
-        self.horn_onset_size = rand.rand_i32(6, 12) as f64;
-        self.horn_tip_size = rand.rand_i32(3, 6) as f64;
-        self.horn_length = rand.rand_i32(50, 100) as f64;
-        self.horn_angle = rand.rand_i32(10, 60) as f64 * DEGREE;
-        self.eye_size = rand.rand_i32(8, 12) as f64;
+    pub fn rand1<R: Rng>(&mut self, rand: &mut R, profile: &GenerationProfile) {
+        self.body_hue = profile.body_hue.draw(rand);
+        self.body_sat = profile.body_sat.draw(rand);
+        self.horn_hue = (self.body_hue + profile.horn_hue_offset.draw(rand)) % 360;
+        self.horn_sat = profile.horn_sat.draw(rand);
+        self.snout_size = profile.snout_size.draw(rand) as f64;
+        self.snout_length = profile.snout_length.draw(rand) as f64;
+        self.head_size = profile.head_size.draw(rand) as f64;
+        self.shoulder_size = profile.shoulder_size.draw(rand) as f64;
+        self.butt_size = profile.butt_size.draw(rand) as f64;
+        self.horn_onset_size = profile.horn_onset_size.draw(rand) as f64;
+        self.horn_tip_size = profile.horn_tip_size.draw(rand) as f64;
+        self.horn_length = profile.horn_length.draw(rand) as f64;
+        self.horn_angle = profile.horn_angle.draw(rand) as f64 * DEGREE;
+        self.eye_size = profile.eye_size.draw(rand) as f64;
         self.iris_size = rand.rand_i32(3, 6) as f64;
         self.iris_hue = rand.rand_i32(70, 270);
         self.iris_sat = rand.rand_i32(40, 70);
-        self.pupil_size = rand.rand_i32(2, 5) as f64;
+        self.pupil_size = profile.pupil_size.draw(rand) as f64;
         let _ = rand.rand_i32(0, 60);
-        self.hair_hue = (self.body_hue + rand.rand_i32(60, 300)) % 360;
-        self.hair_sat = rand.rand_i32(60, 100);
-        self.hair_count = (rand.rand_i32(12, 30) * 2) as usize;
+        self.hair_hue = (self.body_hue + profile.hair_hue_offset.draw(rand)) % 360;
+        self.hair_sat = profile.hair_sat.draw(rand);
+        self.hair_count = (profile.hair_pairs.draw(rand) * 2) as usize;
         self.hair_starts = Vec::with_capacity(self.hair_count);
         self.hair_gammas = Vec::with_capacity(self.hair_count);
         self.hair_lengths = Vec::with_capacity(self.hair_count);
@@ -129,22 +131,22 @@ impl Data {
         self.hair_tip_lightnesses = Vec::with_capacity(self.hair_count);
         self.hair_straightnesses = Vec::with_capacity(self.hair_count);
 
-        self.make_hair1(rand, 0, self.hair_count / 2);
+        self.make_hair1(rand, profile, 0, self.hair_count / 2);
     }
 
-    pub fn rand2(&mut self, rand: &mut Random) {
-        self.make_hair2(rand, 0, self.hair_count / 2);
+    pub fn rand2<R: Rng>(&mut self, rand: &mut R, profile: &GenerationProfile) {
+        self.make_hair2(rand, profile, 0, self.hair_count / 2);
 
-        self.tail_start_size = rand.rand_i32(4, 10) as f64;
-        self.tail_end_size = rand.rand_i32(10, 20) as f64;
-        self.tail_length = rand.rand_i32(100, 150) as f64;
-        self.tail_angle = rand.rand_i32(-20, 45) as f64 * DEGREE;
-        self.tail_gamma = 0.1 + rand.rand() * 6.0;
-        self.brow_size = rand.rand_i32(2, 4) as f64;
-        self.brow_length = 2.0 + rand.rand() * 3.0;
-        self.brow_mood = 2.0 * rand.rand() - 1.0;
+        self.tail_start_size = profile.tail_start_size.draw(rand) as f64;
+        self.tail_end_size = profile.tail_end_size.draw(rand) as f64;
+        self.tail_length = profile.tail_length.draw(rand) as f64;
+        self.tail_angle = profile.tail_angle.draw(rand) as f64 * DEGREE;
+        self.tail_gamma = profile.tail_gamma.draw(rand);
+        self.brow_size = profile.brow_size.draw(rand) as f64;
+        self.brow_length = profile.brow_length.draw(rand);
+        self.brow_mood = profile.brow_mood.draw(rand);
 
-        let neck_tilt = rand.rand_i32(-30, 30);
+        let neck_tilt = profile.neck_tilt.draw(rand);
         self.neck_tilt = neck_tilt as f64 * DEGREE;
 
         let a = neck_tilt / 3;
@@ -158,43 +160,58 @@ impl Data {
         self.face_tilt = face_tilt as f64 * DEGREE;
     }
 
-    pub fn rand3(&mut self, rand: &mut Random) {
-        self.pose = Pose::new(rand);
+    pub fn rand3<R: Rng>(&mut self, rand: &mut R, profile: &GenerationProfile) {
+        self.pose = Pose::new(rand, profile);
     }
 
-    pub fn rand4(&mut self, rand: &mut Random) {
+    pub fn rand4<R: Rng>(&mut self, rand: &mut R, profile: &GenerationProfile) {
         let half_count = self.hair_count / 2;
 
-        self.make_hair1(rand, half_count, half_count);
-        self.make_hair2(rand, half_count, half_count);
+        self.make_hair1(rand, profile, half_count, half_count);
+        self.make_hair2(rand, profile, half_count, half_count);
     }
 
-    pub fn make_hair1(&mut self, rand: &mut Random, start: usize, count: usize) {
+    pub fn make_hair1<R: Rng>(
+        &mut self,
+        rand: &mut R,
+        profile: &GenerationProfile,
+        start: usize,
+        count: usize,
+    ) {
         for _ in start..start + count {
-            self.hair_starts.push(rand.rand_i32(-20, 100) as f64);
+            self.hair_starts.push(profile.hair_start.draw(rand) as f64);
         }
 
         for _ in start..start + count {
-            self.hair_gammas.push(0.3 + rand.rand() * 3.0);
+            self.hair_gammas.push(profile.hair_gamma.draw(rand));
         }
 
         for _ in start..start + count {
-            self.hair_lengths.push(rand.rand_i32(80, 150) as f64);
+            self.hair_lengths
+                .push(profile.hair_length.draw(rand) as f64);
         }
 
         for _ in start..start + count {
             self.hair_angles
-                .push((rand.rand_i32(0, 60) as f64) * DEGREE);
+                .push((profile.hair_angle.draw(rand) as f64) * DEGREE);
         }
     }
 
-    pub fn make_hair2(&mut self, rand: &mut Random, start: usize, count: usize) {
+    pub fn make_hair2<R: Rng>(
+        &mut self,
+        rand: &mut R,
+        profile: &GenerationProfile,
+        start: usize,
+        count: usize,
+    ) {
         for _ in start..start + count {
-            self.hair_tip_lightnesses.push(rand.rand_i32(40, 85));
+            self.hair_tip_lightnesses
+                .push(profile.hair_tip_lightness.draw(rand));
         }
 
         for _ in start..start + count {
-            self.hair_straightnesses.push(rand.rand_i32(-40, 40) as f64);
+            self.hair_straightnesses
+                .push(profile.hair_straightness.draw(rand) as f64);
         }
     }
 }
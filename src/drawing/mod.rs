@@ -120,13 +120,8 @@ fn circle_impl(
         }
 
         for x in left..=right {
-            let color = circle_shading_rgba(
-                (x - cx) as f64,
-                (y - cy) as f64,
-                r as f64,
-                color,
-                coloring,
-            );
+            let color =
+                circle_shading_rgba((x - cx) as f64, (y - cy) as f64, r as f64, color, coloring);
 
             image.put_pixel(x.try_into().unwrap(), y.try_into().unwrap(), color.into());
         }
@@ -0,0 +1,365 @@
+use anyhow::bail;
+use anyhow::Result;
+
+use crate::Rng;
+
+/// An inclusive range of integers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl IntRange {
+    pub fn new(min: i32, max: i32) -> Self {
+        IntRange { min, max }
+    }
+
+    pub fn draw<R: Rng>(&self, rand: &mut R) -> i32 {
+        rand.rand_i32(self.min, self.max)
+    }
+
+    fn validate(&self, name: &str) -> Result<()> {
+        if self.min > self.max {
+            bail!(
+                "{} minimum {} is above maximum {}",
+                name,
+                self.min,
+                self.max
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// A range of floating point numbers from +min+ to +min+ + +width+.
+///
+/// The width is stored instead of the maximum as max - min is not exact, and avatars must not
+/// change by a rounding error.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloatRange {
+    pub min: f64,
+    pub width: f64,
+}
+
+impl FloatRange {
+    pub fn new(min: f64, width: f64) -> Self {
+        FloatRange { min, width }
+    }
+
+    /// A range from +min+ to +max+
+    pub fn between(min: f64, max: f64) -> Self {
+        FloatRange::new(min, max - min)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.min + self.width
+    }
+
+    pub fn draw<R: Rng>(&self, rand: &mut R) -> f64 {
+        self.min + rand.rand() * self.width
+    }
+
+    fn validate(&self, name: &str) -> Result<()> {
+        if !(self.width >= 0.0 && self.min.is_finite() && self.max().is_finite()) {
+            bail!("{} range {} to {} is invalid", name, self.min, self.max());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the index of the choice drawn with the relative +weights+.
+///
+/// Equal weights draw the same number as Rng::choice() so the default profile matches the
+/// original unicornify.
+pub fn choose<R: Rng>(rand: &mut R, weights: &[f64]) -> usize {
+    if weights.iter().all(|w| *w == weights[0]) {
+        return rand.choice(weights.len() as i32) as usize;
+    }
+
+    let total: f64 = weights.iter().sum();
+    let x = rand.rand() * total;
+
+    let mut cumulative = 0.0;
+
+    for (i, w) in weights.iter().enumerate() {
+        cumulative += w;
+
+        if x < cumulative {
+            return i;
+        }
+    }
+
+    // x is at most total, rounding could land it on the last bound
+    weights.iter().rposition(|w| *w > 0.0).unwrap()
+}
+
+fn validate_weights(name: &str, weights: &[f64]) -> Result<()> {
+    if weights.iter().any(|w| !(w.is_finite() && *w >= 0.0)) {
+        bail!("{} weights must be finite and not negative", name);
+    }
+
+    if weights.iter().sum::<f64>() <= 0.0 {
+        bail!("{} weights must not all be zero", name);
+    }
+
+    Ok(())
+}
+
+/// GenerationProfile holds the ranges and weights avatar traits are drawn from.
+///
+/// The default profile reproduces the original unicornify avatars.  Other profiles are still
+/// deterministic per hash as every trait is drawn in the same order, for example
+///
+///     use unicornify::GenerationProfile;
+///     use unicornify::IntRange;
+///
+///     let short_horns = GenerationProfile {
+///         horn_length: IntRange::new(20, 40),
+///         ..GenerationProfile::default()
+///     };
+///
+/// Angles are in degrees.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerationProfile {
+    pub body_hue: IntRange,
+    pub body_sat: IntRange,
+    /// Added to the body hue
+    pub horn_hue_offset: IntRange,
+    pub horn_sat: IntRange,
+    pub snout_size: IntRange,
+    pub snout_length: IntRange,
+    pub head_size: IntRange,
+    pub shoulder_size: IntRange,
+    pub butt_size: IntRange,
+    pub horn_onset_size: IntRange,
+    pub horn_tip_size: IntRange,
+    pub horn_length: IntRange,
+    pub horn_angle: IntRange,
+    pub eye_size: IntRange,
+    pub pupil_size: IntRange,
+    /// Added to the body hue
+    pub hair_hue_offset: IntRange,
+    pub hair_sat: IntRange,
+    /// The mane has twice this many hairs
+    pub hair_pairs: IntRange,
+    pub hair_start: IntRange,
+    pub hair_gamma: FloatRange,
+    pub hair_length: IntRange,
+    pub hair_angle: IntRange,
+    pub hair_tip_lightness: IntRange,
+    pub hair_straightness: IntRange,
+    pub tail_start_size: IntRange,
+    pub tail_end_size: IntRange,
+    pub tail_length: IntRange,
+    pub tail_angle: IntRange,
+    pub tail_gamma: FloatRange,
+    pub brow_size: IntRange,
+    pub brow_length: FloatRange,
+    pub brow_mood: FloatRange,
+    pub neck_tilt: IntRange,
+    /// Weights of Pose::RotaryGallop and Pose::Walk
+    pub pose: [f64; 2],
+
+    pub sky_hue: IntRange,
+    pub sky_sat: IntRange,
+    pub land_hue: IntRange,
+    pub land_sat: IntRange,
+    pub land_light: IntRange,
+    pub horizon: FloatRange,
+    pub rainbow_foot: FloatRange,
+    /// Weights of a rainbow arcing left and right
+    pub rainbow_dir: [f64; 2],
+    pub rainbow_height: FloatRange,
+    pub rainbow_band_width: FloatRange,
+    pub cloud_count: IntRange,
+    pub cloud_lightness: IntRange,
+
+    /// Drawn squared, so smaller unicorns are more likely
+    pub scale_factor: FloatRange,
+    /// Weights of the camera looking from below and above a y_angle of 90 degrees
+    pub camera_side: [f64; 2],
+    /// Degrees away from the side view at 90 degrees
+    pub y_angle: IntRange,
+    pub x_angle: IntRange,
+    pub focal_length: FloatRange,
+}
+
+impl GenerationProfile {
+    /// Returns an error for ranges or weights no trait can be drawn from
+    pub fn validate(&self) -> Result<()> {
+        let ints = [
+            ("Body hue", &self.body_hue),
+            ("Body saturation", &self.body_sat),
+            ("Horn hue offset", &self.horn_hue_offset),
+            ("Horn saturation", &self.horn_sat),
+            ("Snout size", &self.snout_size),
+            ("Snout length", &self.snout_length),
+            ("Head size", &self.head_size),
+            ("Shoulder size", &self.shoulder_size),
+            ("Butt size", &self.butt_size),
+            ("Horn onset size", &self.horn_onset_size),
+            ("Horn tip size", &self.horn_tip_size),
+            ("Horn length", &self.horn_length),
+            ("Horn angle", &self.horn_angle),
+            ("Eye size", &self.eye_size),
+            ("Pupil size", &self.pupil_size),
+            ("Hair hue offset", &self.hair_hue_offset),
+            ("Hair saturation", &self.hair_sat),
+            ("Hair pairs", &self.hair_pairs),
+            ("Hair start", &self.hair_start),
+            ("Hair length", &self.hair_length),
+            ("Hair angle", &self.hair_angle),
+            ("Hair tip lightness", &self.hair_tip_lightness),
+            ("Hair straightness", &self.hair_straightness),
+            ("Tail start size", &self.tail_start_size),
+            ("Tail end size", &self.tail_end_size),
+            ("Tail length", &self.tail_length),
+            ("Tail angle", &self.tail_angle),
+            ("Brow size", &self.brow_size),
+            ("Neck tilt", &self.neck_tilt),
+            ("Sky hue", &self.sky_hue),
+            ("Sky saturation", &self.sky_sat),
+            ("Land hue", &self.land_hue),
+            ("Land saturation", &self.land_sat),
+            ("Land lightness", &self.land_light),
+            ("Cloud count", &self.cloud_count),
+            ("Cloud lightness", &self.cloud_lightness),
+            ("Y angle", &self.y_angle),
+            ("X angle", &self.x_angle),
+        ];
+
+        for (name, range) in ints.iter() {
+            range.validate(name)?;
+        }
+
+        if self.hair_pairs.min < 0 || self.cloud_count.min < 0 {
+            bail!("Hair pairs and cloud count must not be negative");
+        }
+
+        let floats = [
+            ("Hair gamma", &self.hair_gamma),
+            ("Tail gamma", &self.tail_gamma),
+            ("Brow length", &self.brow_length),
+            ("Brow mood", &self.brow_mood),
+            ("Horizon", &self.horizon),
+            ("Rainbow foot", &self.rainbow_foot),
+            ("Rainbow height", &self.rainbow_height),
+            ("Rainbow band width", &self.rainbow_band_width),
+            ("Scale factor", &self.scale_factor),
+            ("Focal length", &self.focal_length),
+        ];
+
+        for (name, range) in floats.iter() {
+            range.validate(name)?;
+        }
+
+        // the background is drawn for hues of a color wheel and a horizon in the lower half
+        let hues = [
+            ("Body hue", &self.body_hue),
+            ("Horn hue offset", &self.horn_hue_offset),
+            ("Hair hue offset", &self.hair_hue_offset),
+            ("Sky hue", &self.sky_hue),
+            ("Land hue", &self.land_hue),
+        ];
+
+        for (name, range) in hues.iter() {
+            if range.min < 0 || range.max >= 360 {
+                bail!(
+                    "{} range {} to {} must be within 0 to 359",
+                    name,
+                    range.min,
+                    range.max
+                );
+            }
+        }
+
+        if self.horizon.min < 0.5 || self.horizon.max() > 1.0 {
+            bail!(
+                "Horizon range {} to {} must be within 0.5 to 1",
+                self.horizon.min,
+                self.horizon.max()
+            );
+        }
+
+        let positive = [
+            ("Rainbow foot", &self.rainbow_foot),
+            ("Rainbow height", &self.rainbow_height),
+            ("Rainbow band width", &self.rainbow_band_width),
+        ];
+
+        for (name, range) in positive.iter() {
+            if range.min <= 0.0 {
+                bail!("{} must be positive, not {}", name, range.min);
+            }
+        }
+
+        validate_weights("Pose", &self.pose)?;
+        validate_weights("Rainbow direction", &self.rainbow_dir)?;
+        validate_weights("Camera side", &self.camera_side)?;
+
+        Ok(())
+    }
+}
+
+impl Default for GenerationProfile {
+    fn default() -> Self {
+        GenerationProfile {
+            body_hue: IntRange::new(0, 359),
+            body_sat: IntRange::new(50, 100),
+            horn_hue_offset: IntRange::new(60, 300),
+            horn_sat: IntRange::new(50, 100),
+            snout_size: IntRange::new(8, 30),
+            snout_length: IntRange::new(70, 110),
+            head_size: IntRange::new(25, 40),
+            shoulder_size: IntRange::new(40, 60),
+            butt_size: IntRange::new(30, 60),
+            horn_onset_size: IntRange::new(6, 12),
+            horn_tip_size: IntRange::new(3, 6),
+            horn_length: IntRange::new(50, 100),
+            horn_angle: IntRange::new(10, 60),
+            eye_size: IntRange::new(8, 12),
+            pupil_size: IntRange::new(2, 5),
+            hair_hue_offset: IntRange::new(60, 300),
+            hair_sat: IntRange::new(60, 100),
+            hair_pairs: IntRange::new(12, 30),
+            hair_start: IntRange::new(-20, 100),
+            hair_gamma: FloatRange::new(0.3, 3.0),
+            hair_length: IntRange::new(80, 150),
+            hair_angle: IntRange::new(0, 60),
+            hair_tip_lightness: IntRange::new(40, 85),
+            hair_straightness: IntRange::new(-40, 40),
+            tail_start_size: IntRange::new(4, 10),
+            tail_end_size: IntRange::new(10, 20),
+            tail_length: IntRange::new(100, 150),
+            tail_angle: IntRange::new(-20, 45),
+            tail_gamma: FloatRange::new(0.1, 6.0),
+            brow_size: IntRange::new(2, 4),
+            brow_length: FloatRange::new(2.0, 3.0),
+            brow_mood: FloatRange::new(-1.0, 2.0),
+            neck_tilt: IntRange::new(-30, 30),
+            pose: [1.0, 1.0],
+
+            sky_hue: IntRange::new(0, 359),
+            sky_sat: IntRange::new(30, 70),
+            land_hue: IntRange::new(0, 359),
+            land_sat: IntRange::new(20, 60),
+            land_light: IntRange::new(20, 50),
+            horizon: FloatRange::new(0.5, 0.2),
+            rainbow_foot: FloatRange::new(0.2, 0.6),
+            rainbow_dir: [1.0, 1.0],
+            rainbow_height: FloatRange::new(0.5, 1.5),
+            rainbow_band_width: FloatRange::new(0.01, 0.02),
+            cloud_count: IntRange::new(1, 3),
+            cloud_lightness: IntRange::new(75, 90),
+
+            scale_factor: FloatRange::new(0.5, 2.5),
+            camera_side: [1.0, 1.0],
+            y_angle: IntRange::new(10, 75),
+            x_angle: IntRange::new(-20, 20),
+            focal_length: FloatRange::new(250.0, 250.0),
+        }
+    }
+}
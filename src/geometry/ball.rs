@@ -2,27 +2,28 @@ use crate::geometry::Axis;
 use crate::geometry::Bone;
 use crate::geometry::Vector;
 use crate::render::GroupTracer;
+use crate::render::TracerArena;
 use crate::render::WorldView;
 use crate::Color;
 
-use std::cell::RefCell;
 use std::ops::Add;
 use std::ops::Div;
 use std::ops::Mul;
 use std::ops::Sub;
-use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::RwLock;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct Ball {
     pub name: String,
-    pub center: Rc<RefCell<Vector>>,
+    pub center: Arc<RwLock<Vector>>,
     pub radius: f64,
     pub color: Color,
 }
 
 impl Ball {
     pub fn new(name: String, x: f64, y: f64, z: f64, radius: f64, color: Color) -> Self {
-        let center = Rc::new(RefCell::new(Vector::new(x, y, z)));
+        let center = Arc::new(RwLock::new(Vector::new(x, y, z)));
 
         Ball {
             name,
@@ -33,7 +34,7 @@ impl Ball {
     }
 
     pub fn new_v(name: String, center: Vector, radius: f64, color: Color) -> Self {
-        let center = Rc::new(RefCell::new(center));
+        let center = Arc::new(RwLock::new(center));
 
         Ball {
             name,
@@ -43,10 +44,15 @@ impl Ball {
         }
     }
 
-    pub fn add_traceable(&self, tracer: &mut GroupTracer, world_view: &WorldView) {
+    pub fn add_traceable(
+        &self,
+        arena: &mut TracerArena,
+        tracer: &mut GroupTracer,
+        world_view: &WorldView,
+    ) {
         let bone = Bone::new(self.clone(), self.clone());
 
-        bone.add_traceable(tracer, world_view);
+        bone.add_traceable(arena, tracer, world_view);
     }
 
     pub fn move_to_sphere(&self, other: &Ball) {
@@ -54,10 +60,10 @@ impl Ball {
     }
 
     pub fn set_distance(&self, distance: f64, other: &Ball) {
-        let span = *self.center.borrow() - *other.center.borrow();
-        let new_center = *other.center.borrow() + span * distance / span.length();
+        let span = *self.center.read().unwrap() - *other.center.read().unwrap();
+        let new_center = *other.center.read().unwrap() + span * distance / span.length();
 
-        self.center.replace(new_center);
+        *self.center.write().unwrap() = new_center;
     }
 
     pub fn set_gap(&self, gap: f64, other: &Ball) {
@@ -65,8 +71,18 @@ impl Ball {
     }
 
     pub fn rotate_around(&self, other: &Vector, angle: f64, axis: Axis) {
-        self.center
-            .replace_with(|&mut old| old.rotate_around(other, angle, axis));
+        let mut center = self.center.write().unwrap();
+
+        *center = center.rotate_around(other, angle, axis);
+    }
+}
+
+impl PartialEq for Ball {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && *self.center.read().unwrap() == *other.center.read().unwrap()
+            && self.radius == other.radius
+            && self.color == other.color
     }
 }
 
@@ -74,7 +90,7 @@ impl Add<Vector> for Ball {
     type Output = Vector;
 
     fn add(self, rhs: Vector) -> Vector {
-        *self.center.borrow() + rhs
+        *self.center.read().unwrap() + rhs
     }
 }
 
@@ -82,7 +98,7 @@ impl Add<Vector> for &Ball {
     type Output = Vector;
 
     fn add(self, rhs: Vector) -> Vector {
-        *self.center.borrow() + rhs
+        *self.center.read().unwrap() + rhs
     }
 }
 
@@ -90,7 +106,7 @@ impl Div<f64> for Ball {
     type Output = Vector;
 
     fn div(self, rhs: f64) -> Vector {
-        *self.center.borrow() / rhs
+        *self.center.read().unwrap() / rhs
     }
 }
 
@@ -98,7 +114,7 @@ impl Mul<f64> for Ball {
     type Output = Vector;
 
     fn mul(self, rhs: f64) -> Vector {
-        *self.center.borrow() * rhs
+        *self.center.read().unwrap() * rhs
     }
 }
 
@@ -106,7 +122,7 @@ impl Sub<Ball> for Ball {
     type Output = Vector;
 
     fn sub(self, rhs: Ball) -> Vector {
-        *self.center.borrow() - *rhs.center.borrow()
+        *self.center.read().unwrap() - *rhs.center.read().unwrap()
     }
 }
 
@@ -114,6 +130,6 @@ impl Sub<Vector> for Ball {
     type Output = Vector;
 
     fn sub(self, rhs: Vector) -> Vector {
-        *self.center.borrow() - rhs
+        *self.center.read().unwrap() - rhs
     }
 }
@@ -6,6 +6,7 @@ use crate::render::BallProjection;
 use crate::render::BoneTracer;
 use crate::render::GroupTracer;
 use crate::render::Tracer;
+use crate::render::TracerArena;
 use crate::render::WorldView;
 
 use std::cell::RefCell;
@@ -33,14 +34,19 @@ impl Bone {
         }
     }
 
-    pub fn add_traceable(&self, tracer: &mut GroupTracer, world_view: &WorldView) {
+    pub fn add_traceable(
+        &self,
+        arena: &mut TracerArena,
+        tracer: &mut GroupTracer,
+        world_view: &WorldView,
+    ) {
         let proj1 = BallProjection::new(world_view, self.b1.clone());
         let proj2 = BallProjection::new(world_view, self.b2.clone());
 
         if self.x_func.is_none() && self.y_func.is_none() {
             let bone_tracer = BoneTracer::new(proj1, proj2);
 
-            tracer.add(Tracer::BoneT(bone_tracer));
+            tracer.add(arena, Tracer::BoneT(bone_tracer));
 
             return;
         }
@@ -94,7 +100,7 @@ impl Bone {
 
             let bone_tracer = BoneTracer::new(prev.borrow().clone(), current.clone());
 
-            tracer.add(Tracer::BoneT(bone_tracer));
+            tracer.add(arena, Tracer::BoneT(bone_tracer));
 
             prev.replace(current.clone());
         }
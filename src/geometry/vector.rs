@@ -6,6 +6,7 @@ use std::ops::Mul;
 use std::ops::Sub;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Vector {
     pub x: f64,
     pub y: f64,
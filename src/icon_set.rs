@@ -0,0 +1,242 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::Avatar;
+use crate::OutputFormat;
+use crate::Provenance;
+use crate::RenderOptions;
+
+use image::RgbaImage;
+
+use std::fs;
+use std::path::PathBuf;
+
+/// The sizes of favicons, touch icons and web app icons
+pub const ICON_SIZES: [u32; 9] = [16, 32, 48, 64, 128, 180, 192, 256, 512];
+
+// The largest image an ICO directory entry can describe
+const ICO_MAX_SIZE: u32 = 256;
+
+/// IconSet renders one avatar as icons for a web site or app.
+///
+/// Every size is rendered from the unicorn itself, not scaled down from a bigger image, so small
+/// icons are as sharp as the renderer can draw them.  write() saves icon-{size}.png for each
+/// size, a favicon.ico holding every size up to 256 pixels and a manifest.json listing them.
+///
+///     use unicornify::Avatar;
+///     use unicornify::IconSet;
+///
+///     let avatar = Avatar::new("58479f76374a3ba3c69b9804163f39f4".to_string(), false).unwrap();
+///
+///     let mut icons = IconSet::new("icons");
+///     icons.sizes = vec![16, 32];
+///
+///     let images = icons.render(&avatar).unwrap();
+///     let ico = IconSet::ico(&images).unwrap();
+#[derive(Clone, Debug)]
+pub struct IconSet {
+    pub sizes: Vec<u32>,
+    pub directory: PathBuf,
+    /// Options for every icon, the size is replaced by each of +sizes+
+    pub options: RenderOptions,
+    pub threads: usize,
+}
+
+impl IconSet {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        IconSet {
+            sizes: ICON_SIZES.to_vec(),
+            directory: directory.into(),
+            options: RenderOptions::default(),
+            threads: 1,
+        }
+    }
+
+    /// Returns an error for icons that cannot be rendered
+    pub fn validate(&self) -> Result<()> {
+        if self.sizes.is_empty() {
+            bail!("At least one size is needed to render icons");
+        }
+
+        if self.threads == 0 {
+            bail!("At least one thread is needed to render icons");
+        }
+
+        if self.options.quadrant.is_some() || self.options.region.is_some() {
+            bail!("Icons show the whole avatar, not a quadrant or region");
+        }
+
+        for (i, size) in self.sizes.iter().enumerate() {
+            if self.sizes[..i].contains(size) {
+                bail!("Icon size {} is given twice", size);
+            }
+
+            let mut options = self.options.clone();
+            options.size = *size;
+
+            options.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders +avatar+ at each of +sizes+, in order
+    pub fn render(&self, avatar: &Avatar) -> Result<Vec<RgbaImage>> {
+        self.validate()?;
+
+        self.sizes
+            .iter()
+            .map(|size| {
+                let mut options = self.options.clone();
+                options.size = *size;
+
+                avatar.render_threaded(&options, self.threads)
+            })
+            .collect()
+    }
+
+    /// Renders +avatar+ and writes the icons and manifest to +directory+.  Returns the paths
+    /// written.  The PNG icons record the hash of the avatar, when it has one, so they can be
+    /// verified.
+    pub fn write(&self, avatar: &Avatar) -> Result<Vec<PathBuf>> {
+        let images = self.render(avatar)?;
+
+        fs::create_dir_all(&self.directory).with_context(|| {
+            format!(
+                "Unable to create output directory {}",
+                self.directory.display()
+            )
+        })?;
+
+        let mut files = Vec::new();
+
+        for (size, image) in self.sizes.iter().zip(images.iter()) {
+            let png = match avatar.hash() {
+                Some(hash) => {
+                    let mut options = self.options.clone();
+                    options.size = *size;
+
+                    Provenance::new(hash, &options).encode_png(image)?
+                }
+                None => OutputFormat::Png.encode(image)?,
+            };
+
+            files.push((png_name(*size), png));
+        }
+
+        let small: Vec<RgbaImage> = images
+            .into_iter()
+            .filter(|i| i.width() <= ICO_MAX_SIZE)
+            .collect();
+
+        if !small.is_empty() {
+            files.push((String::from("favicon.ico"), IconSet::ico(&small)?));
+        }
+
+        files.push((String::from("manifest.json"), self.manifest().into_bytes()));
+
+        let mut written = Vec::new();
+
+        for (name, contents) in files {
+            let path = self.directory.join(name);
+
+            fs::write(&path, contents)
+                .with_context(|| format!("Unable to write {}", path.display()))?;
+
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+
+    /// A web app manifest listing the PNG icons, and favicon.ico when there are sizes up to 256
+    /// pixels
+    pub fn manifest(&self) -> String {
+        let mut icons: Vec<String> = self
+            .sizes
+            .iter()
+            .map(|size| {
+                format!(
+                    "    {{ \"src\": \"{}\", \"sizes\": \"{}x{}\", \"type\": \"image/png\" }}",
+                    png_name(*size),
+                    size,
+                    size
+                )
+            })
+            .collect();
+
+        let ico_sizes: Vec<String> = self
+            .sizes
+            .iter()
+            .filter(|s| **s <= ICO_MAX_SIZE)
+            .map(|s| format!("{}x{}", s, s))
+            .collect();
+
+        if !ico_sizes.is_empty() {
+            icons.push(format!(
+                "    {{ \"src\": \"favicon.ico\", \"sizes\": \"{}\", \"type\": \"image/x-icon\" }}",
+                ico_sizes.join(" ")
+            ));
+        }
+
+        format!("{{\n  \"icons\": [\n{}\n  ]\n}}\n", icons.join(",\n"))
+    }
+
+    /// Packs +images+ into one multi-resolution ICO file.  Each image is stored as a PNG, so
+    /// none may be more than 256 pixels wide or high.
+    pub fn ico(images: &[RgbaImage]) -> Result<Vec<u8>> {
+        if images.is_empty() {
+            bail!("An icon needs at least one image");
+        }
+
+        let mut pngs = Vec::new();
+
+        for image in images.iter() {
+            if image.width() > ICO_MAX_SIZE || image.height() > ICO_MAX_SIZE {
+                bail!(
+                    "Icons are at most {} pixels wide and high, not {}x{}",
+                    ICO_MAX_SIZE,
+                    image.width(),
+                    image.height()
+                );
+            }
+
+            pngs.push(OutputFormat::Png.encode(image)?);
+        }
+
+        // ICONDIR: reserved, type 1 for icons, image count
+        let mut ico = Vec::new();
+        ico.extend_from_slice(&0u16.to_le_bytes());
+        ico.extend_from_slice(&1u16.to_le_bytes());
+        ico.extend_from_slice(&(images.len() as u16).to_le_bytes());
+
+        let mut offset = 6 + 16 * images.len();
+
+        for (image, png) in images.iter().zip(pngs.iter()) {
+            // 256 pixels is written as 0
+            ico.push(image.width() as u8);
+            ico.push(image.height() as u8);
+            // no palette, reserved
+            ico.push(0);
+            ico.push(0);
+            // color planes, bits per pixel
+            ico.extend_from_slice(&1u16.to_le_bytes());
+            ico.extend_from_slice(&32u16.to_le_bytes());
+            ico.extend_from_slice(&(png.len() as u32).to_le_bytes());
+            ico.extend_from_slice(&(offset as u32).to_le_bytes());
+
+            offset += png.len();
+        }
+
+        for png in pngs {
+            ico.extend_from_slice(&png);
+        }
+
+        Ok(ico)
+    }
+}
+
+fn png_name(size: u32) -> String {
+    format!("icon-{}.png", size)
+}
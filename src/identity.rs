@@ -0,0 +1,81 @@
+use anyhow::bail;
+use anyhow::Result;
+
+use md5::Md5;
+use sha2::Digest;
+use sha2::Sha256;
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Digests Gravatar accepts for email addresses
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha256,
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashAlgorithm::Md5 => f.write_str("md5"),
+            HashAlgorithm::Sha256 => f.write_str("sha256"),
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "md5" => Ok(HashAlgorithm::Md5),
+            "sha256" | "sha-256" => Ok(HashAlgorithm::Sha256),
+            _ => bail!("Unknown hash algorithm {}, expected md5 or sha256", s),
+        }
+    }
+}
+
+/// Returns the Gravatar hash of +email+ as lowercase hex.  Like Gravatar the address is trimmed
+/// and lowercased before hashing.
+pub fn email_hash(email: &str, algorithm: HashAlgorithm) -> String {
+    let email = email.trim().to_lowercase();
+
+    match algorithm {
+        HashAlgorithm::Md5 => to_hex(&Md5::digest(email.as_bytes())),
+        HashAlgorithm::Sha256 => to_hex(&Sha256::digest(email.as_bytes())),
+    }
+}
+
+/// Checks that +hash+ is an MD5 or SHA-256 hex digest, that is 32 or 64 hex digits, and returns
+/// it lowercased.
+pub fn parse_hash(hash: &str) -> Result<String> {
+    if let Some(c) = hash.chars().find(|c| !c.is_ascii_hexdigit()) {
+        bail!("Invalid hash {:?}, {:?} is not a hex digit", hash, c);
+    }
+
+    match hash.len() {
+        32 | 64 => Ok(hash.to_ascii_lowercase()),
+        n => bail!(
+            "Invalid hash {:?}, expected 32 (MD5) or 64 (SHA-256) hex digits but got {}",
+            hash,
+            n
+        ),
+    }
+}
+
+/// Returns the avatar hash for +identity+ which is either an email address, hashed with
+/// +algorithm+, or an MD5 or SHA-256 hex digest.
+pub fn identity_hash(identity: &str, algorithm: HashAlgorithm) -> Result<String> {
+    let identity = identity.trim();
+
+    if identity.contains('@') {
+        Ok(email_hash(identity, algorithm))
+    } else {
+        parse_hash(identity)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
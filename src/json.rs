@@ -0,0 +1,265 @@
+use anyhow::bail;
+use anyhow::Result;
+
+use crate::geometry::Point;
+use crate::geometry::Vector;
+use crate::scene::Background;
+use crate::scene::Grass;
+use crate::unicorn::Pose;
+use crate::Color;
+use crate::Data;
+use crate::Parameters;
+
+// A JSON value, written by hand so parameters can be printed without the serde feature
+pub(crate) enum Value {
+    Float(f64),
+    Integer(i64),
+    Text(&'static str),
+    Array(Vec<Value>),
+    Object(Vec<(&'static str, Value)>),
+}
+
+// Converts to the JSON value serde would serialize, field for field
+pub(crate) trait ToJson {
+    fn json(&self) -> Value;
+}
+
+// Pretty prints +value+ with the same layout as serde_json::to_string_pretty()
+pub(crate) fn to_string_pretty(value: &Value) -> Result<String> {
+    let mut json = String::new();
+
+    write(&mut json, "value", value, 0)?;
+
+    Ok(json)
+}
+
+fn write(json: &mut String, key: &str, value: &Value, indent: usize) -> Result<()> {
+    match value {
+        Value::Float(f) => {
+            if !f.is_finite() {
+                bail!("Unable to write {} {} as JSON", key, f);
+            }
+
+            json.push_str(&format!("{:?}", f));
+        }
+        Value::Integer(i) => json.push_str(&i.to_string()),
+        Value::Text(t) => json.push_str(&format!("\"{}\"", t)),
+        Value::Array(values) => {
+            write_all(json, "[", "]", indent, values.iter().map(|v| (key, v, "")))?
+        }
+        Value::Object(fields) => write_all(
+            json,
+            "{",
+            "}",
+            indent,
+            fields.iter().map(|(k, v)| (*k, v, *k)),
+        )?,
+    }
+
+    Ok(())
+}
+
+// Writes the +items+ of an array or object, each is its key for errors, value and the field
+// name to print, if any
+fn write_all<'a>(
+    json: &mut String,
+    open: &str,
+    close: &str,
+    indent: usize,
+    items: impl ExactSizeIterator<Item = (&'a str, &'a Value, &'a str)>,
+) -> Result<()> {
+    json.push_str(open);
+
+    if items.len() == 0 {
+        json.push_str(close);
+
+        return Ok(());
+    }
+
+    for (i, (key, value, name)) in items.enumerate() {
+        json.push_str(if i == 0 { "\n" } else { ",\n" });
+        json.push_str(&" ".repeat(indent + 2));
+
+        if !name.is_empty() {
+            json.push_str(&format!("\"{}\": ", name));
+        }
+
+        write(json, key, value, indent + 2)?;
+    }
+
+    json.push('\n');
+    json.push_str(&" ".repeat(indent));
+    json.push_str(close);
+
+    Ok(())
+}
+
+impl ToJson for f64 {
+    fn json(&self) -> Value {
+        Value::Float(*self)
+    }
+}
+
+impl ToJson for i32 {
+    fn json(&self) -> Value {
+        Value::Integer(*self as i64)
+    }
+}
+
+impl ToJson for u32 {
+    fn json(&self) -> Value {
+        Value::Integer(*self as i64)
+    }
+}
+
+impl ToJson for u8 {
+    fn json(&self) -> Value {
+        Value::Integer(*self as i64)
+    }
+}
+
+impl ToJson for usize {
+    fn json(&self) -> Value {
+        Value::Integer(*self as i64)
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::json).collect())
+    }
+}
+
+impl ToJson for Color {
+    fn json(&self) -> Value {
+        Value::Object(vec![
+            ("r", self.r.json()),
+            ("g", self.g.json()),
+            ("b", self.b.json()),
+            ("a", self.a.json()),
+        ])
+    }
+}
+
+impl ToJson for Point {
+    fn json(&self) -> Value {
+        Value::Object(vec![("x", self.x.json()), ("y", self.y.json())])
+    }
+}
+
+impl ToJson for Vector {
+    fn json(&self) -> Value {
+        Value::Object(vec![
+            ("x", self.x.json()),
+            ("y", self.y.json()),
+            ("z", self.z.json()),
+        ])
+    }
+}
+
+impl ToJson for Pose {
+    fn json(&self) -> Value {
+        let (kind, phase) = match self {
+            Pose::RotaryGallop { phase } => ("RotaryGallop", phase),
+            Pose::Walk { phase } => ("Walk", phase),
+        };
+
+        Value::Object(vec![("kind", Value::Text(kind)), ("phase", phase.json())])
+    }
+}
+
+impl ToJson for Data {
+    fn json(&self) -> Value {
+        Value::Object(vec![
+            ("head_size", self.head_size.json()),
+            ("snout_size", self.snout_size.json()),
+            ("shoulder_size", self.shoulder_size.json()),
+            ("snout_length", self.snout_length.json()),
+            ("butt_size", self.butt_size.json()),
+            ("body_hue", self.body_hue.json()),
+            ("body_sat", self.body_sat.json()),
+            ("horn_hue", self.horn_hue.json()),
+            ("horn_sat", self.horn_sat.json()),
+            ("horn_onset_size", self.horn_onset_size.json()),
+            ("horn_tip_size", self.horn_tip_size.json()),
+            ("horn_length", self.horn_length.json()),
+            ("horn_angle", self.horn_angle.json()),
+            ("eye_size", self.eye_size.json()),
+            ("iris_size", self.iris_size.json()),
+            ("iris_hue", self.iris_hue.json()),
+            ("iris_sat", self.iris_sat.json()),
+            ("pupil_size", self.pupil_size.json()),
+            ("hair_hue", self.hair_hue.json()),
+            ("hair_sat", self.hair_sat.json()),
+            ("hair_count", self.hair_count.json()),
+            ("hair_starts", self.hair_starts.json()),
+            ("hair_gammas", self.hair_gammas.json()),
+            ("hair_lengths", self.hair_lengths.json()),
+            ("hair_angles", self.hair_angles.json()),
+            ("hair_straightnesses", self.hair_straightnesses.json()),
+            ("hair_tip_lightnesses", self.hair_tip_lightnesses.json()),
+            ("tail_start_size", self.tail_start_size.json()),
+            ("tail_end_size", self.tail_end_size.json()),
+            ("tail_length", self.tail_length.json()),
+            ("tail_angle", self.tail_angle.json()),
+            ("tail_gamma", self.tail_gamma.json()),
+            ("brow_size", self.brow_size.json()),
+            ("brow_length", self.brow_length.json()),
+            ("brow_mood", self.brow_mood.json()),
+            ("pose", self.pose.json()),
+            ("neck_tilt", self.neck_tilt.json()),
+            ("face_tilt", self.face_tilt.json()),
+            ("x_angle", self.x_angle.json()),
+            ("y_angle", self.y_angle.json()),
+        ])
+    }
+}
+
+impl ToJson for Background {
+    fn json(&self) -> Value {
+        Value::Object(vec![
+            ("sky_hue", self.sky_hue.json()),
+            ("sky_sat", self.sky_sat.json()),
+            ("land_hue", self.land_hue.json()),
+            ("land_sat", self.land_sat.json()),
+            ("horizon", self.horizon.json()),
+            ("rainbow_foot", self.rainbow_foot.json()),
+            ("rainbow_dir", self.rainbow_dir.json()),
+            ("rainbow_height", self.rainbow_height.json()),
+            ("rainbow_band_width", self.rainbow_band_width.json()),
+            ("cloud_positions", self.cloud_positions.json()),
+            ("cloud_sizes", self.cloud_sizes.json()),
+            ("cloud_lightnesses", self.cloud_lightnesses.json()),
+            ("land_light", self.land_light.json()),
+        ])
+    }
+}
+
+impl ToJson for Grass {
+    fn json(&self) -> Value {
+        Value::Object(vec![
+            ("seed", self.seed.json()),
+            ("row_seed_add", self.row_seed_add.json()),
+            ("horizon", self.horizon.json()),
+            ("blade_height_far", self.blade_height_far.json()),
+            ("blade_height_near", self.blade_height_near.json()),
+            ("wind", self.wind.json()),
+            ("color1", self.color1.json()),
+            ("color2", self.color2.json()),
+            ("min_bottom_y", self.min_bottom_y.json()),
+        ])
+    }
+}
+
+impl ToJson for Parameters {
+    fn json(&self) -> Value {
+        Value::Object(vec![
+            ("data", self.data.json()),
+            ("background", self.background.json()),
+            ("grass", self.grass.json()),
+            ("scale_factor", self.scale_factor.json()),
+            ("focal_length", self.focal_length.json()),
+            ("light_direction", self.light_direction.json()),
+        ])
+    }
+}
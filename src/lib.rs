@@ -1,21 +1,104 @@
+mod animation;
 mod avatar;
+mod avatar_builder;
+mod batch;
+mod cache;
 mod color;
 mod data;
 pub mod drawing;
+mod generation_profile;
 pub mod geometry;
+mod icon_set;
+pub mod identity;
+mod json;
+mod mesh;
+mod output_format;
+mod parameters;
+mod pov;
+mod provenance;
 mod pyrand;
 pub mod render;
+mod render_options;
+mod rng;
 pub mod scene;
+mod server;
 mod sorter;
+mod svg;
+pub mod terminal;
 mod tv;
 pub mod unicorn;
+mod y4m;
 
+pub use animation::Animation;
 pub use avatar::Avatar;
+pub use avatar::RENDER_VERSION;
+pub use avatar_builder::AvatarBuilder;
+pub use batch::Batch;
+pub use batch::BatchFailure;
+pub use batch::BatchSummary;
+pub use cache::AvatarCache;
 pub use color::Color;
 pub use data::Data;
+pub use generation_profile::FloatRange;
+pub use generation_profile::GenerationProfile;
+pub use generation_profile::IntRange;
+pub use icon_set::IconSet;
+pub use icon_set::ICON_SIZES;
+pub use identity::HashAlgorithm;
+pub use mesh::Mesh;
+pub use output_format::OutputFormat;
+pub use output_format::JPEG_QUALITY;
+pub use parameters::Parameters;
+pub use provenance::Provenance;
 pub use pyrand::Random;
+pub use pyrand::RandomState;
+pub use render_options::Region;
+pub use render_options::RenderOptions;
+pub use rng::Rng;
+pub use server::Server;
 pub use sorter::Sorter;
 pub use tv::TV;
+pub use y4m::Y4mWriter;
 
+#[cfg(test)]
+mod test_animation;
+#[cfg(test)]
+mod test_avatar_builder;
+#[cfg(test)]
+mod test_batch;
+#[cfg(test)]
+mod test_bone_tracer;
+#[cfg(test)]
+mod test_cache;
+#[cfg(test)]
+mod test_fixtures;
+#[cfg(test)]
+mod test_generation_profile;
+#[cfg(test)]
+mod test_icon_set;
+#[cfg(test)]
+mod test_identity;
+#[cfg(test)]
+mod test_mesh;
+#[cfg(test)]
+mod test_output_format;
+#[cfg(test)]
+mod test_parameters;
+#[cfg(test)]
+mod test_pov;
+#[cfg(test)]
+mod test_provenance;
 #[cfg(test)]
 mod test_pyrand;
+#[cfg(test)]
+mod test_render_options;
+#[cfg(test)]
+mod test_rng;
+#[cfg(test)]
+mod test_server;
+#[cfg(test)]
+mod test_svg;
+#[cfg(test)]
+mod test_terminal;
+#[cfg(test)]
+mod test_y4m;
@@ -0,0 +1,253 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::geometry::Ball;
+use crate::geometry::Vector;
+use crate::Color;
+
+use std::f64::consts::PI;
+use std::io::Write;
+
+// Vertices around each ring of a capsule
+const SEGMENTS: u32 = 12;
+// Rings from the pole of each ball to the seam where the balls are joined
+const RINGS: u32 = 6;
+
+/// Mesh is a triangle mesh with vertex colors built from the balls and bones of a unicorn.
+///
+/// Positions are in the world coordinates of the tracer where y points down.
+///
+///     use unicornify::Avatar;
+///
+///     let avatar = Avatar::new("58479f76374a3ba3c69b9804163f39f4".to_string(), false).unwrap();
+///
+///     let mut glb = Vec::new();
+///     avatar.mesh().write_glb(&mut glb).unwrap();
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub positions: Vec<Vector>,
+    pub normals: Vec<Vector>,
+    pub colors: Vec<Color>,
+    /// Three vertex indices per triangle, counter-clockwise seen from outside
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Mesh::default()
+    }
+
+    /// Adds the tapered capsule the tracer draws between +b1+ and +b2+, colored like +b1+ at
+    /// one end and +b2+ at the other.  A ball is a bone from the ball to itself.
+    pub fn add_bone(&mut self, b1: &Ball, b2: &Ball) {
+        let c1 = *b1.center.read().unwrap();
+        let c2 = *b2.center.read().unwrap();
+        let (r1, r2) = (b1.radius, b2.radius);
+
+        let span = c2 - c1;
+        let length = span.length();
+
+        // one ball is inside the other, so only the bigger one is seen
+        if length <= (r1 - r2).abs() {
+            let ball = if r1 >= r2 { b1 } else { b2 };
+
+            self.add_bone_between(ball, ball, Vector::new(0.0, -1.0, 0.0), 0.0);
+
+            return;
+        }
+
+        // the normals of the cone touching both balls lean this far towards the smaller ball
+        let seam = ((r1 - r2) / length).asin();
+
+        self.add_bone_between(b1, b2, span / length, seam);
+    }
+
+    /// The number of triangles in the mesh
+    pub fn triangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Writes the mesh as a binary glTF 2.0 file.  The node holding the mesh is turned upside
+    /// down so the unicorn stands upright in y-up viewers, and vertex colors are converted from
+    /// sRGB to the linear colors glTF expects.
+    pub fn write_glb<W: Write>(&self, writer: W) -> Result<()> {
+        if self.indices.is_empty() {
+            bail!("The mesh has no triangles");
+        }
+
+        let vertices = self.positions.len();
+
+        let mut buffer = Vec::new();
+
+        for p in self.positions.iter() {
+            push_vector(&mut buffer, p);
+        }
+
+        for n in self.normals.iter() {
+            push_vector(&mut buffer, n);
+        }
+
+        for c in self.colors.iter() {
+            for channel in [c.r, c.g, c.b].iter() {
+                buffer.extend_from_slice(&(to_linear(*channel) as f32).to_le_bytes());
+            }
+        }
+
+        for i in self.indices.iter() {
+            buffer.extend_from_slice(&i.to_le_bytes());
+        }
+
+        let (min, max) = self.bounds();
+        let vector_bytes = vertices * 12;
+
+        let json = format!(
+            concat!(
+                "{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"unicornify\"}},",
+                "\"scene\":0,\"scenes\":[{{\"nodes\":[0]}}],",
+                "\"nodes\":[{{\"name\":\"unicorn\",\"mesh\":0,\"rotation\":[1,0,0,0]}}],",
+                "\"meshes\":[{{\"primitives\":[{{",
+                "\"attributes\":{{\"POSITION\":0,\"NORMAL\":1,\"COLOR_0\":2}},",
+                "\"indices\":3,\"material\":0}}]}}],",
+                "\"materials\":[{{\"pbrMetallicRoughness\":",
+                "{{\"metallicFactor\":0,\"roughnessFactor\":1}}}}],",
+                "\"buffers\":[{{\"byteLength\":{}}}],",
+                "\"bufferViews\":[",
+                "{{\"buffer\":0,\"byteOffset\":0,\"byteLength\":{},\"target\":34962}},",
+                "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":34962}},",
+                "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":34962}},",
+                "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":34963}}],",
+                "\"accessors\":[",
+                "{{\"bufferView\":0,\"componentType\":5126,\"count\":{},\"type\":\"VEC3\",",
+                "\"min\":[{},{},{}],\"max\":[{},{},{}]}},",
+                "{{\"bufferView\":1,\"componentType\":5126,\"count\":{},\"type\":\"VEC3\"}},",
+                "{{\"bufferView\":2,\"componentType\":5126,\"count\":{},\"type\":\"VEC3\"}},",
+                "{{\"bufferView\":3,\"componentType\":5125,\"count\":{},\"type\":\"SCALAR\"}}]}}"
+            ),
+            buffer.len(),
+            vector_bytes,
+            vector_bytes,
+            vector_bytes,
+            2 * vector_bytes,
+            vector_bytes,
+            3 * vector_bytes,
+            self.indices.len() * 4,
+            vertices,
+            min.x as f32,
+            min.y as f32,
+            min.z as f32,
+            max.x as f32,
+            max.y as f32,
+            max.z as f32,
+            vertices,
+            vertices,
+            self.indices.len(),
+        );
+
+        let mut json = json.into_bytes();
+
+        while json.len() % 4 != 0 {
+            json.push(b' ');
+        }
+
+        while buffer.len() % 4 != 0 {
+            buffer.push(0);
+        }
+
+        let total = 12 + 8 + json.len() + 8 + buffer.len();
+
+        let mut glb = Vec::with_capacity(total);
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(&buffer);
+
+        let mut writer = writer;
+
+        writer.write_all(&glb).context("Unable to write glTF")?;
+        writer.flush().context("Unable to write glTF")
+    }
+
+    // Adds rings of vertices from the far pole of +b1+ along +axis+ to the far pole of +b2+,
+    // switching from +b1+ to +b2+ at +seam+ radians from the equator
+    fn add_bone_between(&mut self, b1: &Ball, b2: &Ball, axis: Vector, seam: f64) {
+        let (ux, uy) = axis.cross_axes();
+        let first = self.positions.len() as u32;
+
+        let mut rings = 0;
+
+        for (ball, from, to) in [(b1, -PI / 2.0, seam), (b2, seam, PI / 2.0)].iter() {
+            let center = *ball.center.read().unwrap();
+
+            for ring in 0..=RINGS {
+                let latitude = from + (to - from) * ring as f64 / RINGS as f64;
+
+                for segment in 0..SEGMENTS {
+                    let longitude = 2.0 * PI * segment as f64 / SEGMENTS as f64;
+
+                    let normal = axis * latitude.sin()
+                        + ux * (latitude.cos() * longitude.cos())
+                        + uy * (latitude.cos() * longitude.sin());
+
+                    self.positions.push(center + normal * ball.radius);
+                    self.normals.push(normal);
+                    self.colors.push(ball.color);
+                }
+
+                rings += 1;
+            }
+        }
+
+        for ring in 0..rings - 1 {
+            let row = first + ring * SEGMENTS;
+            let next_row = row + SEGMENTS;
+
+            for segment in 0..SEGMENTS {
+                let next = (segment + 1) % SEGMENTS;
+
+                self.indices.extend_from_slice(&[
+                    row + segment,
+                    row + next,
+                    next_row + next,
+                    row + segment,
+                    next_row + next,
+                    next_row + segment,
+                ]);
+            }
+        }
+    }
+
+    fn bounds(&self) -> (Vector, Vector) {
+        let mut min = Vector::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut max = Vector::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+
+        for p in self.positions.iter() {
+            min = Vector::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = Vector::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+
+        (min, max)
+    }
+}
+
+fn push_vector(buffer: &mut Vec<u8>, v: &Vector) {
+    for c in [v.x, v.y, v.z].iter() {
+        buffer.extend_from_slice(&(*c as f32).to_le_bytes());
+    }
+}
+
+// Converts an sRGB +channel+ to linear light
+fn to_linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use image::DynamicImage;
+use image::ImageOutputFormat;
+use image::Rgb;
+use image::RgbImage;
+use image::RgbaImage;
+
+use image_webp::ColorType;
+use image_webp::WebPEncoder;
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// JPEG quality used unless another is given
+pub const JPEG_QUALITY: u8 = 90;
+
+/// OutputFormat is an image file format an avatar can be encoded as.
+///
+///     use unicornify::Avatar;
+///     use unicornify::OutputFormat;
+///     use unicornify::RenderOptions;
+///
+///     let avatar = Avatar::new("58479f76374a3ba3c69b9804163f39f4".to_string(), false).unwrap();
+///     let image = avatar.render(&RenderOptions::new(32)).unwrap();
+///
+///     let webp = OutputFormat::WebP.encode(&image).unwrap();
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OutputFormat {
+    Png,
+    /// JPEG at a quality from 1 to 100.  JPEG has no alpha channel so the image is drawn over
+    /// black, transparent pixels become black and partly transparent ones darker.
+    Jpeg(u8),
+    Bmp,
+    Gif,
+    /// A single image icon, at most 256 pixels wide and high
+    Ico,
+    /// Lossless WebP
+    WebP,
+    /// Raw RGBA bytes, four per pixel, row by row without a header
+    Rgba,
+}
+
+impl OutputFormat {
+    /// The format for the extension of +path+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .with_context(|| format!("{} has no image extension", path.display()))?;
+
+        extension
+            .parse()
+            .with_context(|| format!("{} has no known image extension", path.display()))
+    }
+
+    /// The usual file extension of this format, from_path() reads it back
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg(_) => "jpg",
+            OutputFormat::Bmp => "bmp",
+            OutputFormat::Gif => "gif",
+            OutputFormat::Ico => "ico",
+            OutputFormat::WebP => "webp",
+            OutputFormat::Rgba => "rgba",
+        }
+    }
+
+    /// Encodes +image+ in this format
+    pub fn encode(&self, image: &RgbaImage) -> Result<Vec<u8>> {
+        let format = match *self {
+            OutputFormat::Png => ImageOutputFormat::Png,
+            OutputFormat::Jpeg(quality) => {
+                if !(1..=100).contains(&quality) {
+                    bail!("Invalid JPEG quality {}, must be 1 to 100", quality);
+                }
+
+                ImageOutputFormat::Jpeg(quality)
+            }
+            OutputFormat::Bmp => ImageOutputFormat::Bmp,
+            OutputFormat::Gif => ImageOutputFormat::Gif,
+            OutputFormat::Ico => {
+                if image.width() > 256 || image.height() > 256 {
+                    bail!(
+                        "Icons are at most 256 pixels wide and high, not {}x{}",
+                        image.width(),
+                        image.height()
+                    );
+                }
+
+                ImageOutputFormat::Ico
+            }
+            OutputFormat::WebP => return encode_webp(image),
+            OutputFormat::Rgba => return Ok(image.as_raw().clone()),
+        };
+
+        let image = match format {
+            ImageOutputFormat::Jpeg(_) => DynamicImage::ImageRgb8(over_black(image)),
+            _ => DynamicImage::ImageRgba8(image.clone()),
+        };
+
+        let mut encoded = Vec::new();
+
+        image
+            .write_to(&mut encoded, format)
+            .with_context(|| format!("Unable to encode {}", self))?;
+
+        Ok(encoded)
+    }
+}
+
+// Encodes +image+ as lossless WebP, without an alpha channel when it is opaque
+fn encode_webp(image: &RgbaImage) -> Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    let encoder = WebPEncoder::new(&mut encoded);
+    let (width, height) = image.dimensions();
+
+    let result = if image.pixels().all(|p| p[3] == 255) {
+        let rgb = DynamicImage::ImageRgba8(image.clone()).into_rgb8();
+
+        encoder.encode(&rgb, width, height, ColorType::Rgb8)
+    } else {
+        encoder.encode(image.as_raw(), width, height, ColorType::Rgba8)
+    };
+
+    result.context("Unable to encode webp")?;
+
+    Ok(encoded)
+}
+
+// Composites +image+ over black for formats without an alpha channel
+fn over_black(image: &RgbaImage) -> RgbImage {
+    RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let p = image.get_pixel(x, y);
+        let a = p[3] as u32;
+
+        let channel = |c: u8| ((c as u32 * a + 127) / 255) as u8;
+
+        Rgb([channel(p[0]), channel(p[1]), channel(p[2])])
+    })
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg(_) => "jpeg",
+            OutputFormat::Bmp => "bmp",
+            OutputFormat::Gif => "gif",
+            OutputFormat::Ico => "ico",
+            OutputFormat::WebP => "webp",
+            OutputFormat::Rgba => "rgba",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    /// Parses a format name, JPEG uses JPEG_QUALITY
+    fn from_str(s: &str) -> Result<Self> {
+        let format = match s.to_ascii_lowercase().as_str() {
+            "png" => OutputFormat::Png,
+            "jpeg" | "jpg" => OutputFormat::Jpeg(JPEG_QUALITY),
+            "bmp" => OutputFormat::Bmp,
+            "gif" => OutputFormat::Gif,
+            "ico" => OutputFormat::Ico,
+            "webp" => OutputFormat::WebP,
+            "rgba" | "raw" => OutputFormat::Rgba,
+            _ => bail!(
+                "Unknown image format {}, use png, jpeg, bmp, gif, ico, webp or rgba",
+                s
+            ),
+        };
+
+        Ok(format)
+    }
+}
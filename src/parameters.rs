@@ -0,0 +1,329 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::generation_profile::choose;
+use crate::geometry::Vector;
+use crate::geometry::DEGREE;
+use crate::json;
+use crate::json::ToJson;
+use crate::scene::Background;
+use crate::scene::Grass;
+use crate::unicorn::Pose;
+use crate::Color;
+use crate::Data;
+use crate::GenerationProfile;
+use crate::Rng;
+
+use std::fmt;
+
+/// Parameters holds everything generated for an avatar before its unicorn is built.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Parameters {
+    pub data: Data,
+    pub background: Background,
+    pub grass: Grass,
+    pub scale_factor: f64,
+    pub focal_length: f64,
+    pub light_direction: Vector,
+}
+
+impl Parameters {
+    /// Draws the parameters from +rand+ in the same order as the original unicornify so the same
+    /// seed produces the same avatar.  The default +profile+ produces the original avatars.
+    pub fn generate<R: Rng>(rand: &mut R, profile: &GenerationProfile, zoom_out: bool) -> Self {
+        let mut data = Data::new();
+        let mut background = Background::new();
+        let mut grass = Grass::new();
+
+        data.rand1(rand, profile);
+        background.rand1(rand, profile);
+
+        let scale_factor =
+            profile.scale_factor.min + rand.rand().powi(2) * profile.scale_factor.width;
+
+        let scale_factor = if zoom_out { 0.5 } else { scale_factor };
+
+        let sign = choose(rand, &profile.camera_side) as i32 * 2 - 1;
+        let abs = profile.y_angle.draw(rand);
+        data.y_angle = (90 + sign * abs) as f64 * DEGREE;
+        data.x_angle = profile.x_angle.draw(rand) as f64 * DEGREE;
+
+        data.rand2(rand, profile);
+        background.rand2(rand, profile);
+        data.rand3(rand, profile);
+        grass.rand(rand);
+
+        let grass_scale = 1.0 + (scale_factor - 0.5) / 2.5;
+        grass.blade_height_near = (0.02 + 0.02 * rand.rand()) * grass_scale;
+        grass.blade_height_far = grass.blade_height_near / grass_slope(data.x_angle);
+
+        let focal_length = profile.focal_length.draw(rand);
+
+        data.rand4(rand, profile);
+
+        let light_direction = Vector::new(rand.rand() * 16.0 - 8.0, 10.0, rand.rand() * 3.0);
+        let light_direction = Vector::new(light_direction.z, light_direction.y, -light_direction.x);
+
+        // end randomization
+
+        grass.horizon = background.horizon;
+        grass.color1 = Color::hsl(
+            background.land_hue,
+            background.land_sat,
+            background.land_light,
+        );
+        grass.color2 = Color::hsl(
+            background.land_hue,
+            background.land_sat,
+            background.land_light / 2,
+        );
+
+        let mut parameters = Parameters {
+            data,
+            background,
+            grass,
+            scale_factor,
+            focal_length,
+            light_direction,
+        };
+
+        parameters.tilt_head_toward_camera();
+
+        parameters
+    }
+
+    /// Returns an error for parameters no unicorn can be built from, such as a mane with fewer hair
+    /// lengths than hairs
+    pub fn validate(&self) -> Result<()> {
+        let data = &self.data;
+
+        let hairs = [
+            ("starts", data.hair_starts.len()),
+            ("gammas", data.hair_gammas.len()),
+            ("lengths", data.hair_lengths.len()),
+            ("angles", data.hair_angles.len()),
+            ("straightnesses", data.hair_straightnesses.len()),
+            ("tip lightnesses", data.hair_tip_lightnesses.len()),
+        ];
+
+        for (name, count) in hairs.iter() {
+            if *count != data.hair_count {
+                bail!(
+                    "Expected {} hair {} but got {}",
+                    data.hair_count,
+                    name,
+                    count
+                );
+            }
+        }
+
+        let background = &self.background;
+        let clouds = background.cloud_positions.len();
+
+        if background.cloud_sizes.len() != clouds || background.cloud_lightnesses.len() != clouds {
+            bail!(
+                "Expected {} cloud sizes and lightnesses but got {} and {}",
+                clouds,
+                background.cloud_sizes.len(),
+                background.cloud_lightnesses.len()
+            );
+        }
+
+        let hues = [
+            ("Body hue", data.body_hue),
+            ("Horn hue", data.horn_hue),
+            ("Hair hue", data.hair_hue),
+            ("Sky hue", background.sky_hue),
+            ("Land hue", background.land_hue),
+        ];
+
+        for (name, hue) in hues.iter() {
+            if !(0..360).contains(hue) {
+                bail!("{} must be 0 to 359, not {}", name, hue);
+            }
+        }
+
+        if !(0.5..=1.0).contains(&background.horizon) {
+            bail!("Horizon must be 0.5 to 1, not {}", background.horizon);
+        }
+
+        let rainbow = [
+            ("Rainbow foot", background.rainbow_foot),
+            ("Rainbow height", background.rainbow_height),
+            ("Rainbow band width", background.rainbow_band_width),
+        ];
+
+        for (name, value) in rainbow.iter() {
+            if !(*value > 0.0 && value.is_finite()) {
+                bail!("{} must be positive, not {}", name, value);
+            }
+        }
+
+        if !(self.scale_factor > 0.0 && self.scale_factor.is_finite()) {
+            bail!("Scale factor must be positive, not {}", self.scale_factor);
+        }
+
+        if !(self.focal_length > 0.0 && self.focal_length.is_finite()) {
+            bail!("Focal length must be positive, not {}", self.focal_length);
+        }
+
+        Ok(())
+    }
+
+    /// Parses and validates parameters written by to_json()
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        let parameters: Parameters =
+            serde_json::from_str(json).context("Unable to parse avatar parameters")?;
+
+        parameters.validate()?;
+
+        Ok(parameters)
+    }
+
+    /// Returns the parameters as pretty printed JSON.  This works without the serde feature, only
+    /// from_json() needs it.
+    pub fn to_json(&self) -> Result<String> {
+        json::to_string_pretty(&self.json()).context("Unable to write avatar parameters")
+    }
+
+    /// Updates the parameters that depend on the camera after data.x_angle or data.y_angle changed
+    pub fn move_camera(&mut self) {
+        self.grass.blade_height_far = self.grass.blade_height_near / grass_slope(self.data.x_angle);
+
+        self.tilt_head_toward_camera();
+    }
+
+    fn tilt_head_toward_camera(&mut self) {
+        let data = &mut self.data;
+
+        if (data.y_angle - 90.0 * DEGREE) * data.neck_tilt > 0.0 {
+            data.neck_tilt *= -1.0;
+            data.face_tilt *= -1.0;
+        }
+    }
+}
+
+impl fmt::Display for Parameters {
+    /// Lists every parameter, one per line.  Angles are shown in degrees.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = &self.data;
+        // rounded so angles drawn in whole degrees print that way
+        let deg = |radians: f64| (radians / DEGREE * 1e9).round() / 1e9;
+
+        writeln!(f, "unicorn")?;
+        writeln!(f, "  body hue/sat:       {} / {}", d.body_hue, d.body_sat)?;
+        writeln!(f, "  head size:          {}", d.head_size)?;
+        writeln!(f, "  snout size:         {}", d.snout_size)?;
+        writeln!(f, "  snout length:       {}", d.snout_length)?;
+        writeln!(f, "  shoulder size:      {}", d.shoulder_size)?;
+        writeln!(f, "  butt size:          {}", d.butt_size)?;
+        writeln!(f, "  horn hue/sat:       {} / {}", d.horn_hue, d.horn_sat)?;
+        writeln!(f, "  horn onset size:    {}", d.horn_onset_size)?;
+        writeln!(f, "  horn tip size:      {}", d.horn_tip_size)?;
+        writeln!(f, "  horn length:        {}", d.horn_length)?;
+        writeln!(f, "  horn angle:         {}", deg(d.horn_angle))?;
+        writeln!(f, "  eye size:           {}", d.eye_size)?;
+        writeln!(f, "  iris size:          {}", d.iris_size)?;
+        writeln!(f, "  iris hue/sat:       {} / {}", d.iris_hue, d.iris_sat)?;
+        writeln!(f, "  pupil size:         {}", d.pupil_size)?;
+        writeln!(f, "  tail start size:    {}", d.tail_start_size)?;
+        writeln!(f, "  tail end size:      {}", d.tail_end_size)?;
+        writeln!(f, "  tail length:        {}", d.tail_length)?;
+        writeln!(f, "  tail angle:         {}", deg(d.tail_angle))?;
+        writeln!(f, "  tail gamma:         {}", d.tail_gamma)?;
+        writeln!(f, "  brow size:          {}", d.brow_size)?;
+        writeln!(f, "  brow length:        {}", d.brow_length)?;
+        writeln!(f, "  brow mood:          {}", d.brow_mood)?;
+        writeln!(f, "  neck tilt:          {}", deg(d.neck_tilt))?;
+        writeln!(f, "  face tilt:          {}", deg(d.face_tilt))?;
+
+        match d.pose {
+            Pose::RotaryGallop { phase } => {
+                writeln!(f, "  pose:               rotary gallop, phase {}", phase)?
+            }
+            Pose::Walk { phase } => writeln!(f, "  pose:               walk, phase {}", phase)?,
+        }
+
+        writeln!(f, "mane")?;
+        writeln!(f, "  hair hue/sat:       {} / {}", d.hair_hue, d.hair_sat)?;
+        writeln!(f, "  hair count:         {}", d.hair_count)?;
+
+        for i in 0..d.hair_starts.len() {
+            writeln!(
+                f,
+                "  hair {:2}:            start {}, gamma {}, length {}, angle {}, straightness {}, tip lightness {}",
+                i,
+                d.hair_starts[i],
+                d.hair_gammas[i],
+                d.hair_lengths[i],
+                deg(d.hair_angles[i]),
+                d.hair_straightnesses[i],
+                d.hair_tip_lightnesses[i],
+            )?;
+        }
+
+        writeln!(f, "camera")?;
+        writeln!(f, "  x angle:            {}", deg(d.x_angle))?;
+        writeln!(f, "  y angle:            {}", deg(d.y_angle))?;
+        writeln!(f, "  focal length:       {}", self.focal_length)?;
+        writeln!(f, "  scale factor:       {}", self.scale_factor)?;
+        writeln!(
+            f,
+            "  light direction:    {}, {}, {}",
+            self.light_direction.x, self.light_direction.y, self.light_direction.z
+        )?;
+
+        let b = &self.background;
+
+        writeln!(f, "background")?;
+        writeln!(f, "  sky hue/sat:        {} / {}", b.sky_hue, b.sky_sat)?;
+        writeln!(
+            f,
+            "  land hue/sat/light: {} / {} / {}",
+            b.land_hue, b.land_sat, b.land_light
+        )?;
+        writeln!(f, "  horizon:            {}", b.horizon)?;
+        writeln!(f, "  rainbow foot:       {}", b.rainbow_foot)?;
+        writeln!(f, "  rainbow dir:        {}", b.rainbow_dir)?;
+        writeln!(f, "  rainbow height:     {}", b.rainbow_height)?;
+        writeln!(f, "  rainbow band width: {}", b.rainbow_band_width)?;
+
+        for i in 0..b.cloud_positions.len() {
+            writeln!(
+                f,
+                "  cloud {}:            position {}, {}, size {}, {}, lightness {}",
+                i,
+                b.cloud_positions[i].x,
+                b.cloud_positions[i].y,
+                b.cloud_sizes[i].x,
+                b.cloud_sizes[i].y,
+                b.cloud_lightnesses[i],
+            )?;
+        }
+
+        let g = &self.grass;
+        let hex = |c: &Color| format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b);
+
+        writeln!(f, "grass")?;
+        writeln!(f, "  seed:               {}", g.seed)?;
+        writeln!(f, "  row seed add:       {}", g.row_seed_add)?;
+        writeln!(f, "  horizon:            {}", g.horizon)?;
+        writeln!(f, "  blade height near:  {}", g.blade_height_near)?;
+        writeln!(f, "  blade height far:   {}", g.blade_height_far)?;
+        writeln!(f, "  wind:               {}", g.wind)?;
+        writeln!(
+            f,
+            "  colors:             {}, {}",
+            hex(&g.color1),
+            hex(&g.color2)
+        )?;
+        write!(f, "  min bottom y:       {}", g.min_bottom_y)
+    }
+}
+
+fn grass_slope(x_angle: f64) -> f64 {
+    2.0 + 4.0 * (20.0 - x_angle / DEGREE) / 40.0
+}
@@ -0,0 +1,164 @@
+use crate::geometry::Ball;
+use crate::geometry::Vector;
+use crate::Avatar;
+use crate::Color;
+use crate::RenderOptions;
+
+use std::fmt::Write;
+
+/// Writes +avatar+ as a POV-Ray 3.7 scene.  Balls become spheres and bones become linear
+/// sphere_sweeps with a color gradient from one end to the other, in world coordinates.
+///
+/// The camera sees exactly what render() draws for +options+ when the scene is rendered at the
+/// same width and height.  A light at the camera plus ambient light shows parts facing the
+/// camera in their flat colors.  The rainbow, land and clouds of the background are not part
+/// of the scene, only the sky color is.
+pub(crate) fn render(avatar: &Avatar, options: &RenderOptions) -> String {
+    let window = options.window();
+    let (world_view, scale, shift) = avatar.camera(options.size);
+
+    let view = (world_view.look_at_point - world_view.camera_position).unit();
+
+    // the center of the window on the projection plane, pixels are sampled at their top left
+    // corner
+    let center_x = (window.x as f64 + window.width as f64 / 2.0 - 0.5 - shift.x) / scale;
+    let center_y = (window.y as f64 + window.height as f64 / 2.0 - 0.5 - shift.y) / scale;
+
+    let direction =
+        view * world_view.focal_length + world_view.ux * center_x + world_view.uy * center_y;
+    let right = world_view.ux * (window.width as f64 / scale);
+    // image rows go down along uy
+    let up = world_view.uy * (-(window.height as f64) / scale);
+
+    let mut pov = String::new();
+
+    writeln!(pov, "// povray +W{} +H{} +UA", window.width, window.height).unwrap();
+    pov.push_str("#version 3.7;\n\n");
+    pov.push_str("global_settings { assumed_gamma 1.0 }\n\n");
+    pov.push_str("#default { finish { ambient 0.4 diffuse 0.6 } }\n\n");
+
+    writeln!(
+        pov,
+        "camera {{\n  perspective\n  location {}\n  direction {}\n  right {}\n  up {}\n}}\n",
+        v(world_view.camera_position),
+        v(direction),
+        v(right),
+        v(up)
+    )
+    .unwrap();
+
+    writeln!(
+        pov,
+        "light_source {{ {} color rgb 1 }}\n",
+        v(world_view.camera_position)
+    )
+    .unwrap();
+
+    if options.background {
+        let background = &avatar.parameters().background;
+        let sky = Color::hsl(background.sky_hue, background.sky_sat, 60);
+
+        writeln!(pov, "background {{ {} }}\n", srgb(sky)).unwrap();
+    } else {
+        pov.push_str("background { rgbt <0, 0, 0, 1> }\n\n");
+    }
+
+    pov.push_str("#declare Unicorn = union {\n");
+
+    for (b1, b2) in avatar.bones(&world_view).iter() {
+        bone(&mut pov, &b1.base, &b2.base);
+    }
+
+    pov.push_str("}\n\nobject { Unicorn }\n");
+
+    pov
+}
+
+fn bone(pov: &mut String, b1: &Ball, b2: &Ball) {
+    let c1 = *b1.center.read().unwrap();
+    let c2 = *b2.center.read().unwrap();
+    let (r1, r2) = (b1.radius, b2.radius);
+
+    let span = c2 - c1;
+    let length = span.length();
+
+    // one ball is inside the other, so only the bigger one is seen
+    if length <= (r1 - r2).abs() {
+        let (center, ball) = if r1 >= r2 { (c1, b1) } else { (c2, b2) };
+
+        writeln!(
+            pov,
+            "  sphere {{ {}, {} pigment {{ {} }} }}",
+            v(center),
+            f(ball.radius),
+            srgb(ball.color)
+        )
+        .unwrap();
+
+        return;
+    }
+
+    let pigment = if b1.color == b2.color {
+        srgb(b1.color)
+    } else {
+        // a gradient along the bone from the far side of b1 to past the far side of b2, so the
+        // pattern does not repeat on the bone
+        let axis = span / length;
+        let (u1, u2) = axis.cross_axes();
+        let period = r1 + length + r2 + 1.0;
+        let start = c1 - axis * r1;
+
+        format!(
+            "gradient x color_map {{ [0 {}] [{} {}] [{} {}] [1 {}] }} matrix <{}, {}, {}, {}>",
+            srgb(b1.color),
+            f(r1 / period),
+            srgb(b1.color),
+            f((r1 + length) / period),
+            srgb(b2.color),
+            srgb(b2.color),
+            components(axis * period),
+            components(u1 * period),
+            components(u2 * period),
+            components(start)
+        )
+    };
+
+    writeln!(
+        pov,
+        "  sphere_sweep {{ linear_spline 2, {}, {}, {}, {} pigment {{ {} }} }}",
+        v(c1),
+        f(r1),
+        v(c2),
+        f(r2),
+        pigment
+    )
+    .unwrap();
+}
+
+fn srgb(color: Color) -> String {
+    format!(
+        "srgb <{}, {}, {}>",
+        f(color.r as f64 / 255.0),
+        f(color.g as f64 / 255.0),
+        f(color.b as f64 / 255.0)
+    )
+}
+
+fn v(vector: Vector) -> String {
+    format!("<{}>", components(vector))
+}
+
+fn components(vector: Vector) -> String {
+    format!("{}, {}, {}", f(vector.x), f(vector.y), f(vector.z))
+}
+
+// Formats +value+ with at most four decimals
+fn f(value: f64) -> String {
+    let s = format!("{:.4}", value);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+
+    match s {
+        "-0" => String::from("0"),
+        _ => s.to_string(),
+    }
+}
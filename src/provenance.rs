@@ -0,0 +1,366 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::Avatar;
+use crate::OutputFormat;
+use crate::Region;
+use crate::RenderOptions;
+use crate::RENDER_VERSION;
+
+use image::ImageFormat;
+use image::RgbaImage;
+
+use std::fmt;
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// The type, data and end offset of a PNG chunk
+type Chunk<'a> = (&'a [u8], &'a [u8], usize);
+
+const SOFTWARE: &str = "Software";
+const HASH: &str = "Unicornify Hash";
+const RENDER: &str = "Unicornify Render Version";
+const SIZE: &str = "Unicornify Size";
+const QUADRANT: &str = "Unicornify Quadrant";
+const REGION: &str = "Unicornify Region";
+const OPTIONS: &str = "Unicornify Options";
+
+/// Provenance records what rendered an avatar image so it can be rendered again.  It is stored
+/// in PNG tEXt chunks, or iTXt chunks for text that is not Latin-1.
+///
+///     use unicornify::Avatar;
+///     use unicornify::Provenance;
+///     use unicornify::RenderOptions;
+///
+///     let hash = "58479f76374a3ba3c69b9804163f39f4";
+///     let options = RenderOptions::new(32);
+///
+///     let avatar = Avatar::new(hash.to_string(), false).unwrap();
+///     let image = avatar.render(&options).unwrap();
+///
+///     let png = Provenance::new(hash, &options).encode_png(&image).unwrap();
+///
+///     let provenance = Provenance::read(&png).unwrap();
+///     assert_eq!(0, provenance.verify(&png, 1).unwrap());
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Provenance {
+    pub hash: String,
+    /// The unicornify version that rendered the image
+    pub version: String,
+    pub render_version: u32,
+    pub options: RenderOptions,
+}
+
+impl Provenance {
+    /// The provenance of an image this version renders for +hash+ with +options+
+    pub fn new(hash: &str, options: &RenderOptions) -> Self {
+        Provenance {
+            hash: hash.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            render_version: RENDER_VERSION,
+            options: options.clone(),
+        }
+    }
+
+    /// Encodes +image+ in +format+, recording the provenance when it is a PNG
+    pub fn encode(&self, format: OutputFormat, image: &RgbaImage) -> Result<Vec<u8>> {
+        let encoded = format.encode(image)?;
+
+        match format {
+            OutputFormat::Png => self.embed(&encoded),
+            _ => Ok(encoded),
+        }
+    }
+
+    /// Encodes +image+ as a PNG holding the provenance
+    pub fn encode_png(&self, image: &RgbaImage) -> Result<Vec<u8>> {
+        self.encode(OutputFormat::Png, image)
+    }
+
+    /// The keywords and text of the tEXt chunks.  Only enabled options are listed.
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = vec![
+            (SOFTWARE, format!("unicornify {}", self.version)),
+            (HASH, self.hash.clone()),
+            (RENDER, self.render_version.to_string()),
+            (SIZE, self.options.size.to_string()),
+        ];
+
+        if let Some(q) = self.options.quadrant {
+            entries.push((QUADRANT, q.to_string()));
+        }
+
+        if let Some(r) = self.options.region {
+            entries.push((REGION, r.to_string()));
+        }
+
+        entries.push((OPTIONS, self.flags().join(" ")));
+
+        entries
+    }
+
+    /// Copies +png+ with tEXt chunks holding the provenance after the image header.  Text with
+    /// characters outside Latin-1 is written to uncompressed iTXt chunks instead.
+    pub fn embed(&self, png: &[u8]) -> Result<Vec<u8>> {
+        let chunks = chunks(png)?;
+
+        let header_end = match chunks.first() {
+            Some((b"IHDR", _, end)) => *end,
+            _ => bail!("The PNG does not start with an image header"),
+        };
+
+        let mut embedded = png[..header_end].to_vec();
+
+        for (keyword, text) in self.entries() {
+            let mut data = keyword.as_bytes().to_vec();
+            data.push(0);
+
+            if text.chars().all(|c| (c as u32) <= 0xff) {
+                data.extend(text.chars().map(|c| c as u8));
+
+                write_chunk(&mut embedded, b"tEXt", &data);
+            } else {
+                // uncompressed, no language tag or translated keyword
+                data.extend_from_slice(&[0, 0, 0, 0]);
+                data.extend_from_slice(text.as_bytes());
+
+                write_chunk(&mut embedded, b"iTXt", &data);
+            }
+        }
+
+        embedded.extend_from_slice(&png[header_end..]);
+
+        Ok(embedded)
+    }
+
+    /// Reads the provenance from the tEXt or uncompressed iTXt chunks of +png+
+    pub fn read(png: &[u8]) -> Result<Self> {
+        let mut entries = Vec::new();
+
+        for (kind, data, _) in chunks(png)? {
+            let entry = match kind {
+                b"tEXt" => text(data),
+                b"iTXt" => international_text(data),
+                _ => None,
+            };
+
+            entries.extend(entry);
+        }
+
+        let find = |keyword: &str| {
+            entries
+                .iter()
+                .find(|(k, _)| k == keyword)
+                .map(|(_, text)| text.as_str())
+        };
+
+        let hash = match find(HASH) {
+            Some(h) => h.to_string(),
+            None => bail!("The PNG has no unicornify provenance"),
+        };
+
+        let version = match find(SOFTWARE).and_then(|s| s.strip_prefix("unicornify ")) {
+            Some(v) => v.to_string(),
+            None => String::from("unknown"),
+        };
+
+        let number = |keyword: &str| -> Result<Option<u32>> {
+            match find(keyword) {
+                Some(n) => Ok(Some(
+                    n.parse()
+                        .with_context(|| format!("Invalid {} {}", keyword, n))?,
+                )),
+                None => Ok(None),
+            }
+        };
+
+        let render_version = number(RENDER)?.with_context(|| format!("{} is missing", RENDER))?;
+        let size = number(SIZE)?.with_context(|| format!("{} is missing", SIZE))?;
+
+        let mut options = RenderOptions::new(size);
+
+        if let Some(q) = find(QUADRANT) {
+            let quadrant = q
+                .parse()
+                .with_context(|| format!("Invalid {} {}", QUADRANT, q))?;
+
+            options.quadrant = Some(quadrant);
+        }
+
+        if let Some(r) = find(REGION) {
+            options.region = Some(r.parse::<Region>()?);
+        }
+
+        options.background = false;
+
+        for flag in find(OPTIONS).unwrap_or("").split_whitespace() {
+            match flag {
+                "background" => options.background = true,
+                "zoom-out" => options.zoom_out = true,
+                "shading" => options.shading = true,
+                "grass" => options.grass = true,
+                f => bail!("Unknown option {} in {}", f, OPTIONS),
+            }
+        }
+
+        Ok(Provenance {
+            hash,
+            version,
+            render_version,
+            options,
+        })
+    }
+
+    /// Renders the avatar again on +threads+ threads and returns how many pixels of the image in
+    /// +png+ differ from it
+    pub fn verify(&self, png: &[u8], threads: usize) -> Result<usize> {
+        if self.render_version != RENDER_VERSION {
+            bail!(
+                "The image was drawn by render version {}, but this is render version {}",
+                self.render_version,
+                RENDER_VERSION
+            );
+        }
+
+        let image = image::load_from_memory_with_format(png, ImageFormat::Png)
+            .context("Unable to decode PNG")?
+            .into_rgba8();
+
+        let avatar = Avatar::new(self.hash.clone(), self.options.zoom_out)?;
+        let rendered = avatar.render_threaded(&self.options, threads)?;
+
+        if image.dimensions() != rendered.dimensions() {
+            bail!(
+                "The image is {}x{} but renders as {}x{}",
+                image.width(),
+                image.height(),
+                rendered.width(),
+                rendered.height()
+            );
+        }
+
+        let differ = image
+            .pixels()
+            .zip(rendered.pixels())
+            .filter(|(a, b)| a != b)
+            .count();
+
+        Ok(differ)
+    }
+
+    fn flags(&self) -> Vec<&'static str> {
+        let options = &self.options;
+
+        [
+            (options.background, "background"),
+            (options.zoom_out, "zoom-out"),
+            (options.shading, "shading"),
+            (options.grass, "grass"),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| *name)
+        .collect()
+    }
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}px", self.hash, self.options.size)?;
+
+        if let Some(q) = self.options.quadrant {
+            write!(f, ", quadrant {}", q)?;
+        }
+
+        if let Some(r) = self.options.region {
+            write!(f, ", region {}", r)?;
+        }
+
+        for flag in self.flags() {
+            write!(f, ", {}", flag)?;
+        }
+
+        write!(
+            f,
+            " by unicornify {} (render version {})",
+            self.version, self.render_version
+        )
+    }
+}
+
+// The chunks of +png+ in order
+fn chunks(png: &[u8]) -> Result<Vec<Chunk<'_>>> {
+    if !png.starts_with(SIGNATURE) {
+        bail!("Not a PNG image");
+    }
+
+    let mut chunks = Vec::new();
+    let mut offset = SIGNATURE.len();
+
+    while offset < png.len() {
+        if png.len() - offset < 12 {
+            bail!("Truncated PNG chunk at byte {}", offset);
+        }
+
+        let mut length = [0; 4];
+        length.copy_from_slice(&png[offset..offset + 4]);
+        let length = u32::from_be_bytes(length) as usize;
+
+        // length, type, data and CRC
+        let end = offset + 12 + length;
+
+        if end > png.len() {
+            bail!("Truncated PNG chunk at byte {}", offset);
+        }
+
+        chunks.push((&png[offset + 4..offset + 8], &png[offset + 8..end - 4], end));
+
+        offset = end;
+    }
+
+    Ok(chunks)
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+
+    let crc = crc32fast::hash(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// A tEXt keyword and its Latin-1 text
+fn text(data: &[u8]) -> Option<(String, String)> {
+    let nul = data.iter().position(|b| *b == 0)?;
+
+    let latin1 = |bytes: &[u8]| bytes.iter().map(|b| *b as char).collect();
+
+    Some((latin1(&data[..nul]), latin1(&data[nul + 1..])))
+}
+
+// An iTXt keyword and its UTF-8 text, compressed text is skipped
+fn international_text(data: &[u8]) -> Option<(String, String)> {
+    let nul = data.iter().position(|b| *b == 0)?;
+    let keyword = data[..nul].iter().map(|b| *b as char).collect();
+
+    // compression flag and method
+    let rest = data.get(nul + 1..)?;
+
+    if *rest.first()? != 0 {
+        return None;
+    }
+
+    // language tag and translated keyword
+    let mut rest = rest.get(2..)?;
+
+    for _ in 0..2 {
+        let nul = rest.iter().position(|b| *b == 0)?;
+        rest = &rest[nul + 1..];
+    }
+
+    Some((keyword, String::from_utf8(rest.to_vec()).ok()?))
+}
@@ -1,11 +1,18 @@
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 
+use std::collections::HashSet;
 use std::convert::TryInto;
+use std::fmt;
 use std::num::Wrapping;
+use std::str::FromStr;
 
 use num_bigint::BigUint;
 
+use sha2::Digest;
+use sha2::Sha512;
+
 // This is a port of the go pyrand code:
 //
 // https://github.com/drbrain/gopyrand
@@ -82,17 +89,111 @@ const MAG01: [u32; 2] = [0, MATRIX_A];
 
 const MAXWIDTH: u64 = 1 << 53;
 
+// The version of Python's random.getstate() tuple
+const VERSION: u32 = 3;
+
+const TWOPI: f64 = 2.0 * std::f64::consts::PI;
+
+/// A snapshot of a Random generator, see Random::get_state().
+///
+/// Formatting and parsing use the tuple Python's random.getstate() returns, so a state printed
+/// with repr() in Python can be restored here and the other way around.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RandomState {
+    pub state: [u32; N],
+    pub index: usize,
+    pub gauss_next: Option<f64>,
+}
+
+impl fmt::Display for RandomState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, (", VERSION)?;
+
+        for word in self.state.iter() {
+            write!(f, "{}, ", word)?;
+        }
+
+        match self.gauss_next {
+            Some(g) => write!(f, "{}), {})", self.index, py_float_repr(g)),
+            None => write!(f, "{}), None)", self.index),
+        }
+    }
+}
+
+impl FromStr for RandomState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+
+        let version_prefix = format!("({},(", VERSION);
+
+        let body = match s
+            .strip_prefix(&version_prefix)
+            .and_then(|b| b.strip_suffix(')'))
+        {
+            Some(b) => b,
+            None => bail!(
+                "Unsupported random state, expected a version {} tuple",
+                VERSION
+            ),
+        };
+
+        let (words, gauss_next) = match body.rsplit_once("),") {
+            Some(parts) => parts,
+            None => bail!("Invalid random state, missing gauss_next"),
+        };
+
+        let words = words
+            .split(',')
+            .map(|w| w.parse::<u32>())
+            .collect::<std::result::Result<Vec<u32>, _>>()
+            .context("Invalid random state word")?;
+
+        if words.len() != N + 1 {
+            bail!(
+                "Invalid random state, expected {} words but got {}",
+                N + 1,
+                words.len()
+            );
+        }
+
+        let state = words[..N].try_into().unwrap(); // length checked above
+        let index = words[N] as usize;
+
+        let gauss_next = match gauss_next {
+            "None" => None,
+            g => Some(
+                g.parse::<f64>()
+                    .with_context(|| format!("Invalid random state gauss_next {}", g))?,
+            ),
+        };
+
+        Ok(RandomState {
+            state,
+            index,
+            gauss_next,
+        })
+    }
+}
+
 pub struct Random {
     state: [u32; N],
     index: usize,
+    gauss_next: Option<f64>,
 }
 
 impl Random {
     pub fn new() -> Self {
         let state: [u32; N] = [0; N];
         let index = 0;
+        let gauss_next = None;
 
-        Random { state, index }
+        Random {
+            state,
+            index,
+            gauss_next,
+        }
     }
 
     fn init(&mut self, seed: u32) {
@@ -147,12 +248,43 @@ impl Random {
         self.seed_vec(seed)
     }
 
+    /// seed_str seeds with a string the same way Python 3's random.seed() does with its default
+    /// version 2 seeding, so
+    ///
+    ///     use unicornify::Random;
+    ///
+    ///     let mut rand = Random::new();
+    ///     rand.seed_str("hello").unwrap();
+    ///     assert_eq!(0.3537754404730722, rand.rand());
+    ///
+    /// is equivalent to Python's
+    ///
+    /// ```python
+    /// random.seed("hello")
+    /// random.random()
+    /// ```
+    pub fn seed_str(&mut self, seed: &str) -> Result<()> {
+        self.seed_byte_string(seed.as_bytes())
+    }
+
+    /// seed_byte_string seeds with +seed+ the same way Python 3's random.seed() does for bytes
+    /// with version 2 seeding.  The bytes followed by their SHA-512 digest are read as a big
+    /// endian integer which is then used as the seed.
+    ///
+    /// Unlike seed_bytes() this never treats +seed+ as the number itself.
+    pub fn seed_byte_string(&mut self, seed: &[u8]) -> Result<()> {
+        let mut bytes = seed.to_vec();
+        bytes.extend_from_slice(&Sha512::digest(seed));
+
+        self.seed_big_u(BigUint::from_bytes_be(&bytes))
+    }
+
     /// seed_hex_string takes a string of hex digits and seeds the PRNG with the corresponding
     /// number.
     ///
     /// Seeding Pythons PRNG with 0x12345678901337cafe is equivalent to calling
     ///
-    ///     use unicornify::pyrand::Random;
+    ///     use unicornify::Random;
     ///
     ///     let seed = String::from("12345678901337cafe");
     ///     Random::new().seed_hex_string(seed);
@@ -229,13 +361,15 @@ impl Random {
 
         mt[0] = 0x8000_0000; // MSB is 1; assuring non-zero initial array
 
+        self.gauss_next = None;
+
         Ok(())
     }
 
     /// Choice is essentially RandRange with a first argument of 0.  It's provided here as the
     /// equivalent to Python's random.choice(), where:
     ///
-    ///     use unicornify::pyrand::Random;
+    ///     use unicornify::Random;
     ///
     ///     let mut rand = Random::new();
     ///     let l: [u8;3] = [42, 66, 13];
@@ -244,9 +378,9 @@ impl Random {
     ///
     /// is equivalent to Python's
     ///
-    /// '''python
-    /// l := [42, 666, 13]
-    /// c := r.choice(l)
+    /// ```python
+    /// l = [42, 66, 13]
+    /// c = r.choice(l)
     /// ```
     pub fn choice(&mut self, length: i32) -> i32 {
         (self.rand() * length as f64) as i32
@@ -378,6 +512,284 @@ impl Random {
 
         start + (self.rand() * width as f64) as i32
     }
+
+    /// Returns a snapshot of the generator that set_state() restores, like Python's
+    /// random.getstate().
+    pub fn get_state(&self) -> RandomState {
+        RandomState {
+            state: self.state,
+            index: self.index,
+            gauss_next: self.gauss_next,
+        }
+    }
+
+    /// Restores a snapshot from get_state(), like Python's random.setstate().
+    pub fn set_state(&mut self, state: &RandomState) -> Result<()> {
+        if state.index > N {
+            bail!("Invalid random state index {}", state.index);
+        }
+
+        self.state = state.state;
+        self.index = state.index;
+        self.gauss_next = state.gauss_next;
+
+        Ok(())
+    }
+
+    /// Returns a random floating point number N such that a <= N <= b for a <= b and b <= N <= a
+    /// for b < a, like Python's random.uniform().
+    pub fn uniform(&mut self, a: f64, b: f64) -> f64 {
+        a + (b - a) * self.rand()
+    }
+
+    /// Returns a random floating point number N such that low <= N <= high with the given +mode+
+    /// between those bounds, like Python's random.triangular().  A +mode+ of None is the midpoint
+    /// between the bounds.
+    pub fn triangular(&mut self, low: f64, high: f64, mode: Option<f64>) -> f64 {
+        let mut u = self.rand();
+
+        if high == low {
+            return low;
+        }
+
+        let mut c = match mode {
+            Some(mode) => (mode - low) / (high - low),
+            None => 0.5,
+        };
+
+        let (low, high) = if u > c {
+            u = 1.0 - u;
+            c = 1.0 - c;
+            (high, low)
+        } else {
+            (low, high)
+        };
+
+        low + (high - low) * (u * c).sqrt()
+    }
+
+    /// Normal distribution with mean +mu+ and standard deviation +sigma+ using the Kinderman and
+    /// Monahan method, like Python's random.normalvariate().
+    pub fn normal_variate(&mut self, mu: f64, sigma: f64) -> f64 {
+        let nv_magic_const = 4.0 * (-0.5f64).exp() / 2.0f64.sqrt();
+
+        loop {
+            let u1 = self.rand();
+            let u2 = 1.0 - self.rand();
+            let z = nv_magic_const * (u1 - 0.5) / u2;
+            let zz = z * z / 4.0;
+
+            if zz <= -u2.ln() {
+                return mu + z * sigma;
+            }
+        }
+    }
+
+    /// Gaussian distribution with mean +mu+ and standard deviation +sigma+, like Python's
+    /// random.gauss().
+    ///
+    /// Values are generated in pairs so every other call does not advance the generator.
+    /// Seeding discards the pending value.
+    pub fn gauss(&mut self, mu: f64, sigma: f64) -> f64 {
+        let z = match self.gauss_next.take() {
+            Some(z) => z,
+            None => {
+                let x2pi = self.rand() * TWOPI;
+                let g2rad = (-2.0 * (1.0 - self.rand()).ln()).sqrt();
+
+                self.gauss_next = Some(x2pi.sin() * g2rad);
+
+                x2pi.cos() * g2rad
+            }
+        };
+
+        mu + z * sigma
+    }
+
+    /// Exponential distribution with rate +lambd+, like Python's random.expovariate().
+    pub fn expo_variate(&mut self, lambd: f64) -> f64 {
+        -(1.0 - self.rand()).ln() / lambd
+    }
+
+    /// Shuffles +items+ in place, like Python's random.shuffle().
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.rand_below_bits(i + 1);
+
+            items.swap(i, j);
+        }
+    }
+
+    /// Returns +k+ unique items chosen from +population+, like Python's random.sample().
+    ///
+    /// Returns an error if +k+ is larger than the population.
+    pub fn sample<T: Clone>(&mut self, population: &[T], k: usize) -> Result<Vec<T>> {
+        let n = population.len();
+
+        if k > n {
+            bail!("Sample of {} is larger than the population of {}", k, n);
+        }
+
+        // Python picks whichever algorithm needs less memory
+        let mut set_size = 21;
+
+        if k > 5 {
+            set_size += 4usize.pow(((k * 3) as f64).log(4.0).ceil() as u32);
+        }
+
+        let mut result = Vec::with_capacity(k);
+
+        if n <= set_size {
+            let mut pool = population.to_vec();
+
+            for i in 0..k {
+                let j = self.rand_below_bits(n - i);
+
+                result.push(pool[j].clone());
+                pool[j] = pool[n - i - 1].clone();
+            }
+        } else {
+            let mut selected = HashSet::with_capacity(k);
+
+            for _ in 0..k {
+                let mut j = self.rand_below_bits(n);
+
+                while selected.contains(&j) {
+                    j = self.rand_below_bits(n);
+                }
+
+                selected.insert(j);
+                result.push(population[j].clone());
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Returns +k+ items chosen from +population+ with replacement, like Python's
+    /// random.choices().  When +weights+ are given each item is chosen with its relative weight.
+    ///
+    /// Returns an error if the population is empty, +weights+ does not match the population, a
+    /// weight is negative or the weights do not have a positive finite sum.
+    pub fn choices<T: Clone>(
+        &mut self,
+        population: &[T],
+        weights: Option<&[f64]>,
+        k: usize,
+    ) -> Result<Vec<T>> {
+        let n = population.len();
+
+        let weights = match weights {
+            Some(w) => w,
+            None if n == 0 && k > 0 => bail!("Cannot choose from an empty population"),
+            None => {
+                return Ok((0..k)
+                    .map(|_| population[(self.rand() * n as f64).floor() as usize].clone())
+                    .collect());
+            }
+        };
+
+        if weights.len() != n {
+            bail!(
+                "{} weights do not match the population of {}",
+                weights.len(),
+                n
+            );
+        }
+
+        if let Some(w) = weights.iter().find(|w| **w < 0.0) {
+            bail!("Weights must not be negative, not {}", w);
+        }
+
+        let cum_weights: Vec<f64> = weights
+            .iter()
+            .scan(0.0, |total, w| {
+                *total += w;
+                Some(*total)
+            })
+            .collect();
+
+        let total = cum_weights.last().copied().unwrap_or(0.0);
+
+        if total <= 0.0 || !total.is_finite() {
+            bail!(
+                "Total of weights must be greater than zero and finite, not {}",
+                total
+            );
+        }
+
+        let hi = n - 1;
+
+        Ok((0..k)
+            .map(|_| {
+                let x = self.rand() * total;
+                let i = cum_weights[..hi].partition_point(|&c| c <= x);
+
+                population[i].clone()
+            })
+            .collect())
+    }
+
+    /// Returns a usize strictly smaller than +n+ the way Python 3 does, by drawing just enough
+    /// random bits.  Python 2's behavior is in rand_below().
+    fn rand_below_bits(&mut self, n: usize) -> usize {
+        let k = usize::BITS - n.leading_zeros();
+
+        loop {
+            let r = self
+                .rand_bits(k)
+                .iter()
+                .rev()
+                .fold(0u64, |r, &w| r << 32 | w as u64) as usize;
+
+            if r < n {
+                return r;
+            }
+        }
+    }
+}
+
+// Formats +value+ like Python's repr() of a float
+fn py_float_repr(value: f64) -> String {
+    if value.is_nan() {
+        return String::from("nan");
+    }
+
+    if value.is_infinite() {
+        return String::from(if value > 0.0 { "inf" } else { "-inf" });
+    }
+
+    let scientific = format!("{:e}", value);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    if !(-4..16).contains(&exponent) {
+        let sign = if exponent < 0 { '-' } else { '+' };
+
+        return format!("{}e{}{:02}", mantissa, sign, exponent.abs());
+    }
+
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => ("-", m),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+
+    if exponent < 0 {
+        let zeros = "0".repeat((-exponent - 1) as usize);
+
+        format!("{}0.{}{}", sign, zeros, digits)
+    } else {
+        let point = exponent as usize + 1;
+
+        if digits.len() > point {
+            format!("{}{}.{}", sign, &digits[..point], &digits[point..])
+        } else {
+            let zeros = "0".repeat(point - digits.len());
+
+            format!("{}{}{}.0", sign, digits, zeros)
+        }
+    }
 }
 
 fn chunk_to_u32(chunk: &[u8]) -> u32 {
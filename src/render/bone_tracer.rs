@@ -4,7 +4,10 @@ use crate::render::BallProjection;
 use crate::render::Bounds;
 use crate::render::RenderingParameters;
 use crate::render::TraceResult;
-use crate::render::Tracer;
+use crate::render::TracerId;
+
+/// Number of rays traced together by BoneTracer::trace_packet
+pub const PACKET_SIZE: usize = 4;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct BoneTracer {
@@ -87,8 +90,17 @@ impl BoneTracer {
         }
     }
 
-    pub fn prune(&self, rendering_parameters: &RenderingParameters) -> Option<Tracer> {
-        prune_bounds(Tracer::BoneT(self.clone()), rendering_parameters)
+    /// The projections of the balls at either end of the bone
+    pub fn balls(&self) -> (&BallProjection, &BallProjection) {
+        (&self.b1, &self.b2)
+    }
+
+    pub fn prune(
+        &self,
+        id: TracerId,
+        rendering_parameters: &RenderingParameters,
+    ) -> Option<TracerId> {
+        prune_bounds(id, &self.bounds, rendering_parameters)
     }
 
     pub fn trace(&self, _x: f64, _y: f64, ray: Vector) -> TraceResult {
@@ -177,4 +189,114 @@ impl BoneTracer {
 
         Some((z, dir, self.b1.base.color.mix(self.b2.base.color, f)))
     }
+
+    /// Traces PACKET_SIZE rays (usually neighboring pixels) against this bone at once.
+    ///
+    /// The quadratic is solved lane-by-lane over fixed-size arrays so the compiler can vectorize
+    /// it.  Each lane performs exactly the same floating point operations as trace() so the
+    /// results are bit-for-bit identical to tracing each ray separately.
+    pub fn trace_packet(&self, rays: &[Vector; PACKET_SIZE]) -> [TraceResult; PACKET_SIZE] {
+        let mut c3 = [0.0; PACKET_SIZE];
+        let mut c5 = [0.0; PACKET_SIZE];
+
+        for i in 0..PACKET_SIZE {
+            let ray = &rays[i];
+
+            c3[i] = -2.0 * (ray.x * self.w1 + ray.y * self.w2 + ray.z * self.w3);
+            c5[i] = -2.0 * (ray.x * self.a1 + ray.y * self.a2 + ray.z * self.a3);
+        }
+
+        let mut hit = [true; PACKET_SIZE];
+        let mut z = [0.0; PACKET_SIZE];
+        let mut f = [0.0; PACKET_SIZE];
+
+        if self.c2 == 0.0 {
+            f = [if self.dr > 0.0 { 1.0 } else { 0.0 }; PACKET_SIZE];
+        } else {
+            let f_default = if self.dr > 0.0 { 1.0 } else { 0.0 };
+
+            let mut pz = [0.0; PACKET_SIZE];
+            let mut discz = [0.0; PACKET_SIZE];
+
+            for i in 0..PACKET_SIZE {
+                let c7 = c3[i] * self.c2i;
+                let c10 = c5[i] * self.c2i;
+                let c12i = 1.0 / (c7 * c7 / 4.0 - self.c9);
+                let c13 = c7 * self.c8 / 2.0 - c10;
+
+                pz[i] = c13 * c12i;
+                let qz = self.c14 * c12i;
+                discz[i] = pz[i] * pz[i] / 4.0 - qz;
+            }
+
+            for i in 0..PACKET_SIZE {
+                // lanes that miss compute NaN below and are masked out by hit, a NaN
+                // discriminant is not below 0 so it hits like in trace()
+                hit[i] = discz[i] >= 0.0 || discz[i].is_nan();
+
+                let rdiscz = discz[i].sqrt();
+                let z1 = -pz[i] / 2.0 + rdiscz;
+                let z2 = -pz[i] / 2.0 - rdiscz;
+                let f1 = -(c3[i] * z1 + self.c4) / (2.0 * self.c2);
+                let f2 = -(c3[i] * z2 + self.c4) / (2.0 * self.c2);
+
+                let g1 = self.ra + f1 * self.dr >= 0.0;
+                let g2 = self.ra + f2 * self.dr >= 0.0;
+
+                // backside, with the same fallbacks trace() applies when a solution lies on the
+                // wrong side of a cone tip
+                z[i] = if g1 && !g2 { z1 } else { z2 };
+                f[i] = match (g1, g2) {
+                    (true, true) => f2,
+                    (false, true) => f_default,
+                    (true, false) => f1,
+                    (false, false) => f2,
+                };
+            }
+        }
+
+        let mut results = [None; PACKET_SIZE];
+
+        for i in 0..PACKET_SIZE {
+            if !hit[i] {
+                continue;
+            }
+
+            let mut z = z[i];
+            let mut f = f[i];
+
+            if f <= 0.0 || f >= 1.0 {
+                f = 1.0f64.min(0.0f64.max(f));
+                let mut pz = c3[i] * f + c5[i];
+                let mut qz = self.c2 * f * f + self.c4 * f + self.c6;
+                let mut discz = pz * pz / 4.0 - qz;
+
+                if discz < 0.0 {
+                    f = 1.0 - f;
+                    pz = c3[i] * f + c5[i];
+                    qz = self.c2 * f * f + self.c4 * f + self.c6;
+                    discz = pz * pz / 4.0 - qz;
+
+                    if discz < 0.0 {
+                        continue;
+                    }
+
+                    // backside
+                    z = -pz / 2.0 - discz.sqrt();
+                }
+            }
+
+            let m1 = self.a1 + f * self.w1;
+            let m2 = self.a2 + f * self.w2;
+            let m3 = self.a3 + f * self.w3;
+            let m = Vector::new(m1, m2, m3);
+
+            let p = rays[i] * z;
+            let dir = p - m;
+
+            results[i] = Some((z, dir, self.b1.base.color.mix(self.b2.base.color, f)));
+        }
+
+        results
+    }
 }
@@ -4,16 +4,15 @@ use crate::render::Bounds;
 use crate::render::GroupTracer;
 use crate::render::RenderingParameters;
 use crate::render::TraceResult;
-use crate::render::Tracer;
-
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::render::TracerArena;
+use crate::render::TracerId;
+use crate::render::PACKET_SIZE;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct FacetTracer {
     root_count: usize,
     root_count_f: f64,
-    facets: Vec<Option<Rc<RefCell<GroupTracer>>>>,
+    facets: Vec<Option<GroupTracer>>,
     pub bounds: Bounds,
     empty: bool,
 }
@@ -38,10 +37,12 @@ impl FacetTracer {
         self.empty
     }
 
-    pub fn add(&mut self, tracer: Tracer) {
+    /// Adds the tracer +id+ to every facet its bounds overlap.  The facets only record the id so
+    /// a tracer spanning many facets is still stored once in the arena.
+    pub fn add(&mut self, arena: &TracerArena, id: TracerId) {
         self.empty = false;
 
-        let bounds = tracer.bounds();
+        let bounds = arena.get(id).bounds();
 
         let (min_x, min_y) = self.facet_coords(bounds.x_min, bounds.y_min);
         let (max_x, max_y) = self.facet_coords(bounds.x_max, bounds.y_max);
@@ -50,16 +51,9 @@ impl FacetTracer {
             for x in min_x..max_x {
                 let n = y * self.root_count + x;
 
-                let facet = match &self.facets[n] {
-                    None => {
-                        let t = Rc::new(RefCell::new(GroupTracer::new()));
-                        self.facets[n] = Some(t.clone());
-                        t
-                    }
-                    Some(t) => t.clone(),
-                };
-
-                facet.borrow_mut().add(tracer.clone());
+                self.facets[n]
+                    .get_or_insert_with(GroupTracer::new)
+                    .insert(arena, id);
             }
         }
     }
@@ -80,14 +74,45 @@ impl FacetTracer {
         y * self.root_count + x
     }
 
-    pub fn prune(&self, rendering_parameters: &RenderingParameters) -> Option<Tracer> {
-        prune_bounds(Tracer::FacetT(self.clone()), rendering_parameters)
+    pub fn prune(
+        &self,
+        id: TracerId,
+        rendering_parameters: &RenderingParameters,
+    ) -> Option<TracerId> {
+        prune_bounds(id, &self.bounds, rendering_parameters)
     }
 
-    pub fn trace(&self, x: f64, y: f64, ray: Vector) -> TraceResult {
+    pub fn trace(&self, arena: &TracerArena, x: f64, y: f64, ray: Vector) -> TraceResult {
         match &self.facets[self.facet_num(x, y)] {
             None => None,
-            Some(t) => t.borrow().trace(x, y, ray),
+            Some(t) => t.trace(arena, x, y, ray),
+        }
+    }
+
+    /// Traces the pixels +xs+ of row +y+ together with the same result as trace() for each.
+    /// Pixels in different facets are traced one by one.
+    pub fn trace_packet(
+        &self,
+        arena: &TracerArena,
+        xs: &[f64; PACKET_SIZE],
+        y: f64,
+        rays: &[Vector; PACKET_SIZE],
+    ) -> [TraceResult; PACKET_SIZE] {
+        let facet = self.facet_num(xs[0], y);
+
+        if xs.iter().all(|x| self.facet_num(*x, y) == facet) {
+            return match &self.facets[facet] {
+                None => [None; PACKET_SIZE],
+                Some(t) => t.trace_packet(arena, xs, y, rays),
+            };
         }
+
+        let mut results = [None; PACKET_SIZE];
+
+        for (i, result) in results.iter_mut().enumerate() {
+            *result = self.trace(arena, xs[i], y, rays[i]);
+        }
+
+        results
     }
 }
@@ -4,17 +4,20 @@ use crate::render::FacetTracer;
 use crate::render::RenderingParameters;
 use crate::render::TraceResult;
 use crate::render::Tracer;
+use crate::render::TracerArena;
+use crate::render::TracerId;
+use crate::render::PACKET_SIZE;
 use crate::Color;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct GroupTracer {
-    tracers: Vec<Tracer>,
+    tracers: Vec<TracerId>,
     pub bounds: Bounds,
 }
 
 impl GroupTracer {
     pub fn new() -> Self {
-        let tracers: Vec<Tracer> = Vec::new();
+        let tracers: Vec<TracerId> = Vec::new();
         let bounds = Bounds::empty();
 
         GroupTracer { tracers, bounds }
@@ -22,6 +25,7 @@ impl GroupTracer {
 
     pub fn flatten_into_facets(
         &self,
+        arena: &mut TracerArena,
         rendering_parameters: &RenderingParameters,
         facet_tracer: &mut FacetTracer,
     ) {
@@ -29,36 +33,52 @@ impl GroupTracer {
             return;
         }
 
-        for tracer in self.tracers.iter() {
-            match tracer {
+        for id in self.tracers.iter() {
+            match arena.get(*id) {
                 Tracer::GroupT(t) => {
-                    t.flatten_into_facets(rendering_parameters, facet_tracer);
+                    t.clone()
+                        .flatten_into_facets(arena, rendering_parameters, facet_tracer);
                 }
-                _ => flatten_non_group_into_facets(
-                    tracer.clone(),
-                    rendering_parameters,
-                    facet_tracer,
-                ),
+                _ => flatten_non_group_into_facets(arena, *id, rendering_parameters, facet_tracer),
             }
         }
     }
 
-    pub fn add(&mut self, tracer: Tracer) {
-        let bounds = tracer.bounds();
+    /// Stores +tracer+ in +arena+ and adds it to this group
+    pub fn add(&mut self, arena: &mut TracerArena, tracer: Tracer) {
+        let id = arena.add(tracer);
+
+        self.insert(arena, id);
+    }
+
+    /// Adds the tracer +id+ that is already stored in +arena+ to this group
+    pub fn insert(&mut self, arena: &TracerArena, id: TracerId) {
+        let bounds = arena.get(id).bounds();
 
         self.bounds = self.bounds.union(&bounds);
 
-        let index = match self.tracers.binary_search_by(|b| b.bounds().cmp(&bounds)) {
+        let index = match self
+            .tracers
+            .binary_search_by(|b| arena.get(*b).bounds().cmp(&bounds))
+        {
             Ok(i) => i,
             Err(i) => i,
         };
 
-        self.tracers.insert(index, tracer);
+        self.tracers.insert(index, id);
+    }
+
+    /// The ids of the tracers in this group
+    pub fn tracers(&self) -> &[TracerId] {
+        &self.tracers
     }
 
-    pub fn prune(&self, rendering_parameters: &RenderingParameters) -> Option<Tracer> {
+    pub fn prune(
+        &self,
+        arena: &mut TracerArena,
+        rendering_parameters: &RenderingParameters,
+    ) -> Option<TracerId> {
         if !rendering_parameters.contains(&self.bounds) {
-            eprintln!("Group out of bounds");
             return None;
         }
 
@@ -77,22 +97,23 @@ impl GroupTracer {
         };
 
         let mut result = FacetTracer::new(&bounds, 16);
-        self.flatten_into_facets(rendering_parameters, &mut result);
+        self.flatten_into_facets(arena, rendering_parameters, &mut result);
 
         if result.is_empty() {
             return None;
         }
 
-        Some(Tracer::FacetT(result))
+        Some(arena.add(Tracer::FacetT(result)))
     }
 
-    pub fn trace(&self, x: f64, y: f64, ray: Vector) -> TraceResult {
+    pub fn trace(&self, arena: &TracerArena, x: f64, y: f64, ray: Vector) -> TraceResult {
         let mut any = false;
         let mut min_z: f64 = 0.0;
         let mut color = Color::black();
         let mut dir = Vector::zero();
 
-        for tracer in self.tracers.iter() {
+        for id in self.tracers.iter() {
+            let tracer = arena.get(*id);
             let bounds = tracer.bounds();
 
             if bounds.z_max <= 0.0 {
@@ -107,7 +128,7 @@ impl GroupTracer {
                 break;
             }
 
-            match tracer.trace(x, y, ray) {
+            match tracer.trace(arena, x, y, ray) {
                 Some((z, t_dir, t_color)) => {
                     if z > 0.0 {
                         if !any || z < min_z {
@@ -128,24 +149,86 @@ impl GroupTracer {
             None
         }
     }
+
+    /// Traces the pixels +xs+ of row +y+ together with the same result as trace() for each
+    pub fn trace_packet(
+        &self,
+        arena: &TracerArena,
+        xs: &[f64; PACKET_SIZE],
+        y: f64,
+        rays: &[Vector; PACKET_SIZE],
+    ) -> [TraceResult; PACKET_SIZE] {
+        let mut results: [TraceResult; PACKET_SIZE] = [None; PACKET_SIZE];
+        // lanes that found a hit nothing later can be in front of
+        let mut done = [false; PACKET_SIZE];
+
+        for id in self.tracers.iter() {
+            let tracer = arena.get(*id);
+            let bounds = tracer.bounds();
+
+            if bounds.z_max <= 0.0 {
+                continue;
+            }
+
+            let mut wanted = [false; PACKET_SIZE];
+
+            for (i, x) in xs.iter().enumerate() {
+                if done[i] || !bounds.contains_xy(*x, y) {
+                    continue;
+                }
+
+                match results[i] {
+                    Some((min_z, _, _)) if !bounds.contains_points_in_front_of_z(min_z) => {
+                        done[i] = true
+                    }
+                    _ => wanted[i] = true,
+                }
+            }
+
+            if done.iter().all(|d| *d) {
+                break;
+            }
+
+            if !wanted.iter().any(|w| *w) {
+                continue;
+            }
+
+            let traced = tracer.trace_packet(arena, xs, y, rays);
+
+            for (i, result) in traced.iter().enumerate() {
+                if let (true, Some((z, _, _))) = (wanted[i], result) {
+                    let nearer = match results[i] {
+                        Some((min_z, _, _)) => *z < min_z,
+                        None => true,
+                    };
+
+                    if *z > 0.0 && nearer {
+                        results[i] = *result;
+                    }
+                }
+            }
+        }
+
+        results
+    }
 }
 
 fn flatten_non_group_into_facets(
-    tracer: Tracer,
+    arena: &mut TracerArena,
+    id: TracerId,
     rendering_parameters: &RenderingParameters,
     facet_tracer: &mut FacetTracer,
 ) {
-    match tracer.prune(rendering_parameters) {
+    match Tracer::prune(arena, id, rendering_parameters) {
         None => {
             return;
         }
-        Some(pruned) => match pruned {
-            Tracer::GroupT(t) => t.flatten_into_facets(rendering_parameters, facet_tracer),
-            Tracer::BoneT(_) => facet_tracer.add(pruned.clone()),
-            Tracer::FacetT(_) => facet_tracer.add(pruned.clone()),
-            Tracer::QuadrantT(_) => facet_tracer.add(pruned.clone()),
-            Tracer::ScalingT(_) => facet_tracer.add(pruned.clone()),
-            Tracer::TranslatingT(_) => facet_tracer.add(pruned.clone()),
+        Some(pruned) => match arena.get(pruned) {
+            Tracer::GroupT(t) => {
+                t.clone()
+                    .flatten_into_facets(arena, rendering_parameters, facet_tracer)
+            }
+            _ => facet_tracer.add(arena, pruned),
         },
     }
 }
@@ -8,11 +8,13 @@ mod rendering_parameters;
 mod scaling_tracer;
 mod sphere_projection;
 mod tracer;
+mod tracer_arena;
 mod translating_tracer;
 mod world_view;
 
 pub use ball_projection::BallProjection;
 pub use bone_tracer::BoneTracer;
+pub use bone_tracer::PACKET_SIZE;
 pub use bounds::Bounds;
 pub use facet_tracer::FacetTracer;
 pub use group_tracer::GroupTracer;
@@ -21,6 +23,8 @@ pub use rendering_parameters::RenderingParameters;
 pub use scaling_tracer::ScalingTracer;
 pub use sphere_projection::SphereProjection;
 pub use tracer::Tracer;
+pub use tracer_arena::TracerArena;
+pub use tracer_arena::TracerId;
 pub use translating_tracer::TranslatingTracer;
 pub use world_view::WorldView;
 
@@ -29,9 +33,13 @@ use crate::Color;
 
 pub type TraceResult = Option<(f64, Vector, Color)>;
 
-pub fn prune_bounds(tracer: Tracer, rendering_parameters: &RenderingParameters) -> Option<Tracer> {
-    if rendering_parameters.contains(&tracer.bounds()) {
-        Some(tracer)
+pub fn prune_bounds(
+    id: TracerId,
+    bounds: &Bounds,
+    rendering_parameters: &RenderingParameters,
+) -> Option<TracerId> {
+    if rendering_parameters.contains(bounds) {
+        Some(id)
     } else {
         None
     }
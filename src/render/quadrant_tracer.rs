@@ -6,12 +6,15 @@ use crate::render::Bounds;
 use crate::render::RenderingParameters;
 use crate::render::TraceResult;
 use crate::render::Tracer;
+use crate::render::TracerArena;
+use crate::render::TracerId;
 use crate::render::TranslatingTracer;
 use crate::render::WorldView;
+use crate::render::PACKET_SIZE;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct QuadrantTracer {
-    source: Box<Tracer>,
+    source: TracerId,
     pub bounds: Bounds,
     x_range: Range<f64>,
     y_range: Range<f64>,
@@ -19,8 +22,14 @@ pub struct QuadrantTracer {
 }
 
 impl QuadrantTracer {
-    pub fn new(world_view: &WorldView, source: Tracer, size: u32, quadrant: u8) -> Self {
-        let bounds = source.bounds();
+    pub fn new(
+        arena: &mut TracerArena,
+        world_view: &WorldView,
+        source: TracerId,
+        size: u32,
+        quadrant: u8,
+    ) -> Self {
+        let bounds = arena.get(source).bounds();
 
         let offset = size as f64;
 
@@ -33,8 +42,8 @@ impl QuadrantTracer {
         };
 
         let shift = Point::new(-x_offset, -y_offset);
-        let translater = TranslatingTracer::new(&world_view, source, shift);
-        let source = Box::new(Tracer::TranslatingT(translater));
+        let translater = TranslatingTracer::new(arena, &world_view, source, shift);
+        let source = arena.add(Tracer::TranslatingT(translater));
 
         let world_view = world_view.clone();
 
@@ -47,26 +56,41 @@ impl QuadrantTracer {
         }
     }
 
-    pub fn prune(&self, rendering_parameters: &RenderingParameters) -> Option<Tracer> {
-        match self.source.prune(rendering_parameters) {
+    pub fn prune(
+        &self,
+        arena: &mut TracerArena,
+        _id: TracerId,
+        rendering_parameters: &RenderingParameters,
+    ) -> Option<TracerId> {
+        match Tracer::prune(arena, self.source, rendering_parameters) {
             None => None,
             Some(pruned) => {
-                let bounds = pruned.bounds();
+                let bounds = arena.get(pruned).bounds();
 
                 let tracer = QuadrantTracer {
-                    source: Box::new(pruned),
+                    source: pruned,
                     bounds: bounds,
                     x_range: self.x_range.clone(),
                     y_range: self.y_range.clone(),
                     world_view: self.world_view.clone(),
                 };
 
-                Some(Tracer::QuadrantT(tracer))
+                Some(arena.add(Tracer::QuadrantT(tracer)))
             }
         }
     }
 
-    pub fn trace(&self, x: f64, y: f64, ray: Vector) -> TraceResult {
-        self.source.trace(x, y, ray)
+    pub fn trace(&self, arena: &TracerArena, x: f64, y: f64, ray: Vector) -> TraceResult {
+        arena.get(self.source).trace(arena, x, y, ray)
+    }
+
+    pub fn trace_packet(
+        &self,
+        arena: &TracerArena,
+        xs: &[f64; PACKET_SIZE],
+        y: f64,
+        rays: &[Vector; PACKET_SIZE],
+    ) -> [TraceResult; PACKET_SIZE] {
+        arena.get(self.source).trace_packet(arena, xs, y, rays)
     }
 }
@@ -3,19 +3,22 @@ use crate::render::Bounds;
 use crate::render::RenderingParameters;
 use crate::render::TraceResult;
 use crate::render::Tracer;
+use crate::render::TracerArena;
+use crate::render::TracerId;
 use crate::render::WorldView;
+use crate::render::PACKET_SIZE;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ScalingTracer {
-    source: Box<Tracer>,
+    source: TracerId,
     scale: f64,
     pub bounds: Bounds,
     world_view: WorldView,
 }
 
 impl ScalingTracer {
-    pub fn new(world_view: &WorldView, source: Tracer, scale: f64) -> Self {
-        let mut bounds = source.bounds();
+    pub fn new(arena: &TracerArena, world_view: &WorldView, source: TracerId, scale: f64) -> Self {
+        let mut bounds = arena.get(source).bounds();
 
         if !bounds.empty {
             bounds.x_min *= scale;
@@ -24,8 +27,6 @@ impl ScalingTracer {
             bounds.y_max *= scale;
         }
 
-        let source = Box::new(source);
-
         let world_view = world_view.clone();
 
         ScalingTracer {
@@ -36,32 +37,66 @@ impl ScalingTracer {
         }
     }
 
-    pub fn prune(&self, rendering_parameters: &RenderingParameters) -> Option<Tracer> {
+    pub fn prune(
+        &self,
+        arena: &mut TracerArena,
+        id: TracerId,
+        rendering_parameters: &RenderingParameters,
+    ) -> Option<TracerId> {
         let scaled = rendering_parameters.scale(self.scale);
 
-        match self.source.prune(&scaled) {
+        match Tracer::prune(arena, self.source, &scaled) {
             None => None,
             Some(pruned) => {
-                if *self.source == pruned {
-                    Some(Tracer::ScalingT(self.clone()))
+                if self.source == pruned {
+                    Some(id)
                 } else {
-                    let tracer = ScalingTracer::new(&self.world_view, pruned, self.scale);
+                    let tracer = ScalingTracer::new(arena, &self.world_view, pruned, self.scale);
 
-                    Some(Tracer::ScalingT(tracer))
+                    Some(arena.add(Tracer::ScalingT(tracer)))
                 }
             }
         }
     }
 
-    pub fn trace(&self, x: f64, y: f64, _ray: Vector) -> TraceResult {
+    pub fn trace(&self, arena: &TracerArena, x: f64, y: f64, _ray: Vector) -> TraceResult {
         let x = x / self.scale;
         let y = y / self.scale;
         let ray = self.world_view.ray(x, y);
 
-        let (z, dir, color) = self.source.trace(x, y, ray)?;
+        let (z, dir, color) = arena.get(self.source).trace(arena, x, y, ray)?;
 
         let z = z * self.scale;
 
         Some((z, dir, color))
     }
+
+    /// Traces the pixels +xs+ of row +y+ together with the same result as trace() for each
+    pub fn trace_packet(
+        &self,
+        arena: &TracerArena,
+        xs: &[f64; PACKET_SIZE],
+        y: f64,
+        _rays: &[Vector; PACKET_SIZE],
+    ) -> [TraceResult; PACKET_SIZE] {
+        let y = y / self.scale;
+
+        let mut scaled = [0.0; PACKET_SIZE];
+        let mut rays = [Vector::zero(); PACKET_SIZE];
+
+        for (i, x) in xs.iter().enumerate() {
+            scaled[i] = x / self.scale;
+            rays[i] = self.world_view.ray(scaled[i], y);
+        }
+
+        let mut results = arena
+            .get(self.source)
+            .trace_packet(arena, &scaled, y, &rays);
+
+        for (z, _, _) in results.iter_mut().flatten() {
+            *z *= self.scale;
+        }
+
+        results
+    }
 }
@@ -7,11 +7,21 @@ use crate::render::QuadrantTracer;
 use crate::render::RenderingParameters;
 use crate::render::ScalingTracer;
 use crate::render::TraceResult;
+use crate::render::TracerArena;
+use crate::render::TracerId;
 use crate::render::TranslatingTracer;
 use crate::render::WorldView;
+use crate::render::PACKET_SIZE;
+
+use crate::Color;
 
 use image::RgbaImage;
 
+use std::ops::Range;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::thread;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Tracer {
     BoneT(BoneTracer),
@@ -34,68 +44,238 @@ impl Tracer {
         }
     }
 
-    pub fn draw(&mut self, world_view: WorldView, image_buffer: &mut RgbaImage) {
+    pub fn draw(
+        arena: &mut TracerArena,
+        id: TracerId,
+        world_view: WorldView,
+        image_buffer: &mut RgbaImage,
+    ) {
         let bounds = image_buffer.into();
 
-        self.draw_partial(world_view, image_buffer, &bounds);
+        Tracer::draw_partial(arena, id, world_view, image_buffer, &bounds);
     }
 
     pub fn draw_partial(
-        &mut self,
+        arena: &mut TracerArena,
+        id: TracerId,
         world_view: WorldView,
         image_buffer: &mut RgbaImage,
         bounds: &Bounds,
     ) {
-        let rect = bounds.intersection(&self.bounds());
-        eprintln!("rect_{:?}", rect);
+        Tracer::trace_partial(arena, id, &world_view, bounds, |x, y, color| {
+            image_buffer.put_pixel(x, y, color.into());
+        });
+    }
 
-        let rendering_parameters = RenderingParameters::new(1.0, rect.clone());
+    /// Draws the part of +image_buffer+ inside +window+ on +threads+ threads.
+    ///
+    /// The tracer is pruned once to the whole image and its rows are split into bands the threads
+    /// trace, so the pixels are the same as drawing the whole image on one thread.
+    pub fn draw_parallel(
+        arena: &mut TracerArena,
+        id: TracerId,
+        world_view: WorldView,
+        image_buffer: &mut RgbaImage,
+        window: &Bounds,
+        threads: usize,
+    ) {
+        let bounds: Bounds = (&mut *image_buffer).into();
+
+        let (pruned, rect) = match Tracer::prune_to(arena, id, &bounds) {
+            Some(p) => p,
+            None => return,
+        };
+
+        let rect = rect.intersection(window);
+
+        if rect.empty {
+            return;
+        }
 
-        match self.prune(&rendering_parameters) {
-            Some(pruned) => {
-                let x_min = rect.clone().x_min as u32;
-                let x_max = rect.clone().x_max as u32;
-                let y_min = rect.clone().y_min as u32;
-                let y_max = rect.clone().y_max as u32;
-
-                for y in y_min..y_max {
-                    for x in x_min..x_max {
-                        let fx = x as f64;
-                        let fy = y as f64;
-                        let ray = world_view.ray(fx, fy);
-
-                        match pruned.trace(fx, fy, ray) {
-                            Some((_, _, color)) => {
-                                image_buffer.put_pixel(x, y, color.into());
+        let arena = &*arena;
+        let world_view = &world_view;
+
+        let x_min = rect.x_min as u32;
+        let x_max = rect.x_max as u32;
+        let y_min = rect.y_min as u32;
+        let y_max = (rect.y_max as u32).max(y_min);
+        let rows = y_max - y_min;
+
+        // several bands per thread so a thread that drew mostly sky can pick up more work
+        let band_count = (threads * 4).min(rows as usize).max(1) as u32;
+        let band_height = rows.div_ceil(band_count);
+
+        let next_band = AtomicU32::new(0);
+
+        let pixels: Vec<(u32, u32, Color)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut pixels = Vec::new();
+
+                        loop {
+                            let band = next_band.fetch_add(1, Ordering::Relaxed);
+
+                            if band >= band_count {
+                                break;
                             }
-                            None => (),
+
+                            let band_min = y_min + band * band_height;
+                            let band_max = (band_min + band_height).min(y_max);
+
+                            Tracer::trace_rows(
+                                arena,
+                                pruned,
+                                world_view,
+                                x_min..x_max,
+                                band_min..band_max,
+                                |x, y, color| pixels.push((x, y, color)),
+                            );
                         }
+
+                        pixels
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
+
+        for (x, y, color) in pixels {
+            image_buffer.put_pixel(x, y, color.into());
+        }
+    }
+
+    fn trace_partial<F: FnMut(u32, u32, Color)>(
+        arena: &mut TracerArena,
+        id: TracerId,
+        world_view: &WorldView,
+        bounds: &Bounds,
+        put_pixel: F,
+    ) {
+        if let Some((pruned, rect)) = Tracer::prune_to(arena, id, bounds) {
+            Tracer::trace_rows(
+                arena,
+                pruned,
+                world_view,
+                rect.x_min as u32..rect.x_max as u32,
+                rect.y_min as u32..rect.y_max as u32,
+                put_pixel,
+            );
+        }
+    }
+
+    // Prunes +id+ to the part of +bounds+ it covers, returning the pruned tracer and that part
+    fn prune_to(
+        arena: &mut TracerArena,
+        id: TracerId,
+        bounds: &Bounds,
+    ) -> Option<(TracerId, Bounds)> {
+        let rect = bounds.intersection(&arena.get(id).bounds());
+
+        let rendering_parameters = RenderingParameters::new(1.0, rect.clone());
+
+        Tracer::prune(arena, id, &rendering_parameters).map(|pruned| (pruned, rect))
+    }
+
+    fn trace_rows<F: FnMut(u32, u32, Color)>(
+        arena: &TracerArena,
+        id: TracerId,
+        world_view: &WorldView,
+        xs: Range<u32>,
+        ys: Range<u32>,
+        mut put_pixel: F,
+    ) {
+        let tracer = arena.get(id);
+        let packet = PACKET_SIZE as u32;
+
+        for y in ys {
+            let fy = y as f64;
+            let mut x = xs.start;
+
+            // neighboring pixels are traced together, the rest of the row one by one
+            while x + packet <= xs.end {
+                let mut fxs = [0.0; PACKET_SIZE];
+                let mut rays = [Vector::zero(); PACKET_SIZE];
+
+                for (i, fx) in fxs.iter_mut().enumerate() {
+                    *fx = (x + i as u32) as f64;
+                    rays[i] = world_view.ray(*fx, fy);
+                }
+
+                let results = tracer.trace_packet(arena, &fxs, fy, &rays);
+
+                for (i, result) in results.iter().enumerate() {
+                    if let Some((_, _, color)) = result {
+                        put_pixel(x + i as u32, y, *color);
                     }
                 }
+
+                x += packet;
+            }
+
+            for x in x..xs.end {
+                let fx = x as f64;
+                let ray = world_view.ray(fx, fy);
+
+                match tracer.trace(arena, fx, fy, ray) {
+                    Some((_, _, color)) => put_pixel(x, y, color),
+                    None => (),
+                }
             }
-            None => (),
         }
     }
 
-    pub fn prune(&self, rendering_parameters: &RenderingParameters) -> Option<Tracer> {
-        match self {
-            Tracer::BoneT(t) => t.prune(rendering_parameters),
-            Tracer::FacetT(t) => t.prune(rendering_parameters),
-            Tracer::GroupT(t) => t.prune(rendering_parameters),
-            Tracer::QuadrantT(t) => t.prune(rendering_parameters),
-            Tracer::ScalingT(t) => t.prune(rendering_parameters),
-            Tracer::TranslatingT(t) => t.prune(rendering_parameters),
+    /// Prunes the tracer +id+ to +rendering_parameters+.  Returns +id+ itself when nothing was
+    /// removed, otherwise the id of a new, smaller tracer added to +arena+.
+    ///
+    /// The tracers that own children are cloned before pruning, which only copies the ids of
+    /// their children, so +arena+ can grow while pruning.
+    pub fn prune(
+        arena: &mut TracerArena,
+        id: TracerId,
+        rendering_parameters: &RenderingParameters,
+    ) -> Option<TracerId> {
+        match arena.get(id) {
+            Tracer::BoneT(t) => t.prune(id, rendering_parameters),
+            Tracer::FacetT(t) => t.prune(id, rendering_parameters),
+            Tracer::GroupT(t) => t.clone().prune(arena, rendering_parameters),
+            Tracer::QuadrantT(t) => t.clone().prune(arena, id, rendering_parameters),
+            Tracer::ScalingT(t) => t.clone().prune(arena, id, rendering_parameters),
+            Tracer::TranslatingT(t) => t.clone().prune(arena, id, rendering_parameters),
         }
     }
 
-    pub fn trace(&self, x: f64, y: f64, ray: Vector) -> TraceResult {
+    pub fn trace(&self, arena: &TracerArena, x: f64, y: f64, ray: Vector) -> TraceResult {
         match self {
             Tracer::BoneT(t) => t.trace(x, y, ray),
-            Tracer::FacetT(t) => t.trace(x, y, ray),
-            Tracer::GroupT(t) => t.trace(x, y, ray),
-            Tracer::QuadrantT(t) => t.trace(x, y, ray),
-            Tracer::ScalingT(t) => t.trace(x, y, ray),
-            Tracer::TranslatingT(t) => t.trace(x, y, ray),
+            Tracer::FacetT(t) => t.trace(arena, x, y, ray),
+            Tracer::GroupT(t) => t.trace(arena, x, y, ray),
+            Tracer::QuadrantT(t) => t.trace(arena, x, y, ray),
+            Tracer::ScalingT(t) => t.trace(arena, x, y, ray),
+            Tracer::TranslatingT(t) => t.trace(arena, x, y, ray),
+        }
+    }
+
+    /// Traces the pixels +xs+ of row +y+ together.  Each pixel gets exactly the result trace()
+    /// gives it.
+    pub fn trace_packet(
+        &self,
+        arena: &TracerArena,
+        xs: &[f64; PACKET_SIZE],
+        y: f64,
+        rays: &[Vector; PACKET_SIZE],
+    ) -> [TraceResult; PACKET_SIZE] {
+        match self {
+            Tracer::BoneT(t) => t.trace_packet(rays),
+            Tracer::FacetT(t) => t.trace_packet(arena, xs, y, rays),
+            Tracer::GroupT(t) => t.trace_packet(arena, xs, y, rays),
+            Tracer::QuadrantT(t) => t.trace_packet(arena, xs, y, rays),
+            Tracer::ScalingT(t) => t.trace_packet(arena, xs, y, rays),
+            Tracer::TranslatingT(t) => t.trace_packet(arena, xs, y, rays),
         }
     }
 }
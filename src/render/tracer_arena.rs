@@ -0,0 +1,39 @@
+use crate::render::Tracer;
+
+/// Index of a Tracer stored in a TracerArena
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TracerId(usize);
+
+/// TracerArena owns every tracer built for one render.  Groups, facets and the wrapping tracers
+/// refer to their children by TracerId so adding a tracer to many facets or pruning a tree never
+/// copies a subtree.
+#[derive(Clone, Debug, Default)]
+pub struct TracerArena {
+    tracers: Vec<Tracer>,
+}
+
+impl TracerArena {
+    pub fn new() -> Self {
+        let tracers = Vec::new();
+
+        TracerArena { tracers }
+    }
+
+    pub fn add(&mut self, tracer: Tracer) -> TracerId {
+        self.tracers.push(tracer);
+
+        TracerId(self.tracers.len() - 1)
+    }
+
+    pub fn get(&self, id: TracerId) -> &Tracer {
+        &self.tracers[id.0]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracers.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracers.len()
+    }
+}
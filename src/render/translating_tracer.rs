@@ -4,19 +4,27 @@ use crate::render::Bounds;
 use crate::render::RenderingParameters;
 use crate::render::TraceResult;
 use crate::render::Tracer;
+use crate::render::TracerArena;
+use crate::render::TracerId;
 use crate::render::WorldView;
+use crate::render::PACKET_SIZE;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct TranslatingTracer {
-    source: Box<Tracer>,
+    source: TracerId,
     pub shift: Point,
     pub bounds: Bounds,
     world_view: WorldView,
 }
 
 impl TranslatingTracer {
-    pub fn new(world_view: &WorldView, source: Tracer, shift: Point) -> Self {
-        let mut bounds = source.bounds();
+    pub fn new(
+        arena: &TracerArena,
+        world_view: &WorldView,
+        source: TracerId,
+        shift: Point,
+    ) -> Self {
+        let mut bounds = arena.get(source).bounds();
 
         if !bounds.empty {
             bounds.x_min += shift.x;
@@ -25,7 +33,6 @@ impl TranslatingTracer {
             bounds.y_max += shift.y;
         }
 
-        let source = Box::new(source);
         let world_view = world_view.clone();
 
         TranslatingTracer {
@@ -36,32 +43,60 @@ impl TranslatingTracer {
         }
     }
 
-    pub fn prune(&self, rendering_parameters: &RenderingParameters) -> Option<Tracer> {
+    pub fn prune(
+        &self,
+        arena: &mut TracerArena,
+        id: TracerId,
+        rendering_parameters: &RenderingParameters,
+    ) -> Option<TracerId> {
         let shifted = rendering_parameters.translated(self.shift.x, self.shift.y);
 
-        match self.source.prune(&shifted) {
+        match Tracer::prune(arena, self.source, &shifted) {
             None => None,
             Some(pruned) => {
-                if *self.source == pruned {
-                    Some(Tracer::TranslatingT(self.clone()))
+                if self.source == pruned {
+                    Some(id)
                 } else {
                     let source = pruned;
                     let shift = self.shift.clone();
 
-                    let tracer = TranslatingTracer::new(&self.world_view, source, shift);
+                    let tracer = TranslatingTracer::new(arena, &self.world_view, source, shift);
 
-                    Some(Tracer::TranslatingT(tracer))
+                    Some(arena.add(Tracer::TranslatingT(tracer)))
                 }
             }
         }
     }
 
-    pub fn trace(&self, x: f64, y: f64, _ray: Vector) -> TraceResult {
+    pub fn trace(&self, arena: &TracerArena, x: f64, y: f64, _ray: Vector) -> TraceResult {
         let x = x - self.shift.x;
         let y = y - self.shift.y;
 
         let ray = self.world_view.ray(x, y);
 
-        self.source.trace(x, y, ray)
+        arena.get(self.source).trace(arena, x, y, ray)
+    }
+
+    /// Traces the pixels +xs+ of row +y+ together with the same result as trace() for each
+    pub fn trace_packet(
+        &self,
+        arena: &TracerArena,
+        xs: &[f64; PACKET_SIZE],
+        y: f64,
+        _rays: &[Vector; PACKET_SIZE],
+    ) -> [TraceResult; PACKET_SIZE] {
+        let y = y - self.shift.y;
+
+        let mut shifted = [0.0; PACKET_SIZE];
+        let mut rays = [Vector::zero(); PACKET_SIZE];
+
+        for (i, x) in xs.iter().enumerate() {
+            shifted[i] = x - self.shift.x;
+            rays[i] = self.world_view.ray(shifted[i], y);
+        }
+
+        arena
+            .get(self.source)
+            .trace_packet(arena, &shifted, y, &rays)
     }
 }
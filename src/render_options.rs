@@ -0,0 +1,148 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use std::fmt;
+use std::str::FromStr;
+
+/// RenderOptions holds everything besides the hash that changes how an avatar looks.  It is
+/// hashable so rendered images can be cached by hash and options.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RenderOptions {
+    /// Width and height of the full image in pixels
+    pub size: u32,
+    /// Render only this quadrant (1 to 4, left to right, top to bottom) of the full image
+    pub quadrant: Option<u8>,
+    /// Render only this rectangle of the full image
+    pub region: Option<Region>,
+    pub background: bool,
+    /// Applied when the Avatar is created, see Avatar::new()
+    pub zoom_out: bool,
+    pub shading: bool,
+    pub grass: bool,
+}
+
+impl RenderOptions {
+    pub fn new(size: u32) -> Self {
+        RenderOptions {
+            size,
+            ..RenderOptions::default()
+        }
+    }
+
+    /// Returns an error for options the renderer cannot draw
+    pub fn validate(&self) -> Result<()> {
+        if self.size == 0 {
+            bail!("Image size must be at least 1 pixel");
+        }
+
+        if let Some(q) = self.quadrant {
+            if !(1..=4).contains(&q) {
+                bail!("Invalid quadrant {}, must be 1 to 4", q);
+            }
+
+            if self.size < 2 {
+                bail!("Image size must be at least 2 pixels to render a quadrant");
+            }
+        }
+
+        if let Some(region) = self.region {
+            if self.quadrant.is_some() {
+                bail!("A region and a quadrant cannot be rendered together");
+            }
+
+            if region.width == 0 || region.height == 0 {
+                bail!("Region {} is empty", region);
+            }
+
+            if region.x as u64 + region.width as u64 > self.size as u64
+                || region.y as u64 + region.height as u64 > self.size as u64
+            {
+                bail!("Region {} is outside the {}px image", region, self.size);
+            }
+        }
+
+        if self.shading {
+            bail!("Shading is not implemented yet");
+        }
+
+        if self.grass {
+            bail!("Grass is not implemented yet");
+        }
+
+        Ok(())
+    }
+
+    /// The rectangle of the full image that is drawn, the quadrant or region if there is one
+    pub(crate) fn window(&self) -> Region {
+        let half = self.size / 2;
+
+        match (self.quadrant, self.region) {
+            (Some(q), _) => {
+                let x = if q == 2 || q == 4 { half } else { 0 };
+                let y = if q >= 3 { half } else { 0 };
+
+                Region::new(x, y, half, half)
+            }
+            (None, Some(region)) => region,
+            (None, None) => Region::new(0, 0, self.size, self.size),
+        }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            size: 128,
+            quadrant: None,
+            region: None,
+            background: true,
+            zoom_out: false,
+            shading: false,
+            grass: false,
+        }
+    }
+}
+
+/// A rectangle of the full image in pixels, written as X,Y,WIDTH,HEIGHT
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+impl FromStr for Region {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let values = s
+            .split(',')
+            .map(|v| v.trim().parse::<u32>())
+            .collect::<std::result::Result<Vec<u32>, _>>()
+            .with_context(|| format!("Invalid region {}, expected X,Y,WIDTH,HEIGHT", s))?;
+
+        match values[..] {
+            [x, y, width, height] => Ok(Region::new(x, y, width, height)),
+            _ => bail!("Invalid region {}, expected X,Y,WIDTH,HEIGHT", s),
+        }
+    }
+}
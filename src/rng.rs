@@ -0,0 +1,46 @@
+use crate::Random;
+
+/// Rng is a deterministic source of random numbers for avatar generation.
+///
+/// Random, the port of Python's Mersenne Twister, is the default and the only generator that
+/// produces the same avatars as unicornify.pictures.  Any other source that repeats its output for
+/// the same seed will produce stable avatars too.
+pub trait Rng {
+    /// Returns the next random floating point number in the range [0.0, 1.0).
+    fn rand(&mut self) -> f64;
+
+    /// Returns +k+ random bits packed into u32s, least significant first.
+    fn rand_bits(&mut self, k: u32) -> Vec<u32>;
+
+    /// Returns a random integer in range [a, b], including both end points.
+    fn rand_i32(&mut self, a: i32, b: i32) -> i32 {
+        if a > b {
+            panic!("empty range for randrange")
+        }
+
+        a + (self.rand() * (b - a + 1) as f64) as i32
+    }
+
+    /// Returns a random index into a sequence of +length+ items.
+    fn choice(&mut self, length: i32) -> i32 {
+        (self.rand() * length as f64) as i32
+    }
+}
+
+impl Rng for Random {
+    fn rand(&mut self) -> f64 {
+        Random::rand(self)
+    }
+
+    fn rand_bits(&mut self, k: u32) -> Vec<u32> {
+        Random::rand_bits(self, k)
+    }
+
+    fn rand_i32(&mut self, a: i32, b: i32) -> i32 {
+        Random::rand_i32(self, a, b)
+    }
+
+    fn choice(&mut self, length: i32) -> i32 {
+        Random::choice(self, length)
+    }
+}
@@ -1,14 +1,18 @@
 use crate::drawing::ColoringParameters;
 use crate::drawing::*;
+use crate::generation_profile::choose;
 use crate::geometry::Point;
 use crate::Color;
-use crate::Random;
+use crate::GenerationProfile;
+use crate::Rng;
 
 use image::Rgba;
 use image::RgbaImage;
 
 use std::convert::TryInto;
 
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Background {
     pub sky_hue: i32,
     pub sky_sat: i32,
@@ -61,7 +65,14 @@ impl Background {
         }
     }
 
-    fn draw_cloud(&self, image: &mut RgbaImage, i: usize, shaded: bool, fsize: f64, quadrant: Option<u8>) {
+    fn draw_cloud(
+        &self,
+        image: &mut RgbaImage,
+        i: usize,
+        shaded: bool,
+        fsize: f64,
+        quadrant: Option<u8>,
+    ) {
         let image_size: u32 = image.width();
         let position = &self.cloud_positions[i];
         let size = &self.cloud_sizes[i];
@@ -128,7 +139,7 @@ impl Background {
         let land_a = Color::hsl(self.land_hue, self.land_sat, self.land_light);
         let land_b = Color::hsl(self.land_hue, self.land_sat, self.land_light / 2);
         let (offset_x, offset_y) = offset(image.width(), quadrant);
-        let edge = horizon - offset_y;
+        let edge = horizon.saturating_sub(offset_y);
 
         for x in 0..image.height() {
             let color = land_a.mix(land_b, (x + offset_x) as f64 / fsize);
@@ -209,21 +220,21 @@ impl Background {
         }
     }
 
-    pub fn rand1(&mut self, rand: &mut Random) {
-        self.sky_hue = rand.rand_i32(0, 359);
-        self.sky_sat = rand.rand_i32(30, 70);
-        self.land_hue = rand.rand_i32(0, 359);
-        self.land_sat = rand.rand_i32(20, 60);
-        self.horizon = 0.5 + rand.rand() * 0.2;
-        self.rainbow_foot = 0.2 + rand.rand() * 0.6;
-        self.rainbow_dir = (rand.choice(2) * 2 - 1) as f64;
-        self.rainbow_height = 0.5 + rand.rand() * 1.5;
-        self.rainbow_band_width = 0.01 + rand.rand() * 0.02;
-        self.land_light = rand.rand_i32(20, 50);
+    pub fn rand1<R: Rng>(&mut self, rand: &mut R, profile: &GenerationProfile) {
+        self.sky_hue = profile.sky_hue.draw(rand);
+        self.sky_sat = profile.sky_sat.draw(rand);
+        self.land_hue = profile.land_hue.draw(rand);
+        self.land_sat = profile.land_sat.draw(rand);
+        self.horizon = profile.horizon.draw(rand);
+        self.rainbow_foot = profile.rainbow_foot.draw(rand);
+        self.rainbow_dir = (choose(rand, &profile.rainbow_dir) as i32 * 2 - 1) as f64;
+        self.rainbow_height = profile.rainbow_height.draw(rand);
+        self.rainbow_band_width = profile.rainbow_band_width.draw(rand);
+        self.land_light = profile.land_light.draw(rand);
     }
 
-    pub fn rand2(&mut self, rand: &mut Random) {
-        let cloud_count: usize = rand.rand_i32(1, 3) as usize;
+    pub fn rand2<R: Rng>(&mut self, rand: &mut R, profile: &GenerationProfile) {
+        let cloud_count: usize = profile.cloud_count.draw(rand) as usize;
 
         self.cloud_positions.reserve(cloud_count);
         self.cloud_sizes.reserve(cloud_count);
@@ -240,7 +251,8 @@ impl Background {
         }
 
         for _ in 0..cloud_count {
-            self.cloud_lightnesses.push(rand.rand_i32(75, 90));
+            self.cloud_lightnesses
+                .push(profile.cloud_lightness.draw(rand));
         }
     }
 }
@@ -1,6 +1,8 @@
 use crate::Color;
-use crate::Random;
+use crate::Rng;
 
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Grass {
     pub seed: u32,
     pub row_seed_add: u32,
@@ -28,7 +30,7 @@ impl Grass {
         }
     }
 
-    pub fn rand(&mut self, rand: &mut Random) {
+    pub fn rand<R: Rng>(&mut self, rand: &mut R) {
         let r = rand.rand_bits(64);
         self.seed = r[0];
         self.row_seed_add = r[1];
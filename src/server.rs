@@ -0,0 +1,302 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::identity::parse_hash;
+use crate::AvatarCache;
+use crate::RenderOptions;
+use crate::RENDER_VERSION;
+
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::Shutdown;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_SIZE: u32 = 128;
+const DEFAULT_WORKERS: usize = 16;
+const MAX_REQUEST: u64 = 8 * 1024;
+const TIMEOUT: Duration = Duration::from_secs(10);
+// rejects run on the accept thread, so a slow client may only hold it up briefly
+const REJECT_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Server answers unicornify.pictures style avatar requests over HTTP/1.1 from an AvatarCache.
+///
+/// ```text
+/// GET /avatar/<hash>?s=<size>&background=0&zoomout=1
+/// ```
+///
+/// The size defaults to 128 and is clamped to +max_size+.  Images never change for the same
+/// hash, options, RENDER_VERSION and crate version, so they are sent with an ETag and a long
+/// Cache-Control max-age.
+///
+/// At most +workers+ connections are answered at once, more are told 503 Service Unavailable.
+pub struct Server {
+    cache: AvatarCache,
+    max_size: u32,
+    workers: usize,
+}
+
+// An HTTP response, the body is left out for HEAD requests
+struct Response {
+    status: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+}
+
+impl Server {
+    pub fn new(cache: AvatarCache, max_size: u32) -> Self {
+        Server {
+            cache,
+            max_size,
+            workers: DEFAULT_WORKERS,
+        }
+    }
+
+    /// Answers at most +workers+ connections at once
+    pub fn with_workers(self, workers: usize) -> Self {
+        Server {
+            workers: workers.max(1),
+            ..self
+        }
+    }
+
+    /// Answers connections to +listener+ until accepting one fails, each on its own thread
+    pub fn serve(&self, listener: TcpListener) -> Result<()> {
+        let busy = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let stream = stream.context("Unable to accept connection")?;
+
+                if busy.fetch_add(1, Ordering::SeqCst) >= self.workers {
+                    busy.fetch_sub(1, Ordering::SeqCst);
+
+                    let _ = reject(stream);
+
+                    continue;
+                }
+
+                let worker = Worker(&busy);
+
+                scope.spawn(move || {
+                    let _worker = worker;
+
+                    // the client hung up or sent garbage, there is nobody to tell
+                    let _ = self.handle(stream);
+                });
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Answers the single request on +stream+ and closes it
+    pub fn handle(&self, stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        let mut reader = BufReader::new((&stream).take(MAX_REQUEST));
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        let mut if_none_match = None;
+
+        loop {
+            let mut header = String::new();
+
+            if reader.read_line(&mut header)? == 0 {
+                bail!("Request headers ended early");
+            }
+
+            let header = header.trim_end();
+
+            if header.is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("if-none-match") {
+                    if_none_match = Some(value.trim().to_string());
+                }
+            }
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or("");
+        let target = parts.next().unwrap_or("");
+
+        let response = match method {
+            "GET" | "HEAD" => self.respond(target, if_none_match.as_deref()),
+            _ => Response::error("405 Method Not Allowed", "Only GET and HEAD are supported"),
+        };
+
+        response.write(&stream, method == "HEAD")
+    }
+
+    fn respond(&self, target: &str, if_none_match: Option<&str>) -> Response {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        let hash = match path.strip_prefix("/avatar/") {
+            Some(h) => h.strip_suffix(".png").unwrap_or(h),
+            None => return Response::error("404 Not Found", "Not found"),
+        };
+
+        let hash = match parse_hash(hash) {
+            Ok(h) => h,
+            Err(e) => return Response::error("400 Bad Request", &format!("{:#}", e)),
+        };
+
+        let options = match self.options(query) {
+            Ok(o) => o,
+            Err(e) => return Response::error("400 Bad Request", &format!("{:#}", e)),
+        };
+
+        // the PNG records the crate version that rendered it
+        let etag = format!(
+            "\"{}-{}-{}-{}-{}{}\"",
+            hash,
+            env!("CARGO_PKG_VERSION"),
+            RENDER_VERSION,
+            options.size,
+            if options.background { 'b' } else { '-' },
+            if options.zoom_out { 'z' } else { '-' },
+        );
+
+        let headers = vec![
+            ("ETag", etag.clone()),
+            ("Cache-Control", String::from("public, max-age=31536000")),
+        ];
+
+        if if_none_match
+            .is_some_and(|tags| tags.split(',').any(|t| t.trim() == etag || t.trim() == "*"))
+        {
+            return Response {
+                status: "304 Not Modified",
+                headers,
+                body: Vec::new(),
+            };
+        }
+
+        match self.cache.image(&hash, &options) {
+            Ok(png) => {
+                let mut headers = headers;
+                headers.push(("Content-Type", String::from("image/png")));
+
+                Response {
+                    status: "200 OK",
+                    headers,
+                    body: png.to_vec(),
+                }
+            }
+            Err(e) => Response::error("500 Internal Server Error", &format!("{:#}", e)),
+        }
+    }
+
+    // Reads the render options from the query string of a request
+    fn options(&self, query: &str) -> Result<RenderOptions> {
+        let mut options = RenderOptions::new(DEFAULT_SIZE);
+
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+
+            match name {
+                "s" | "size" => {
+                    options.size = value
+                        .parse()
+                        .with_context(|| format!("Invalid size {}", value))?
+                }
+                "background" => options.background = flag(name, value)?,
+                "zoomout" => options.zoom_out = flag(name, value)?,
+                // unknown parameters are ignored like unicornify.pictures does
+                _ => (),
+            }
+        }
+
+        if options.size == 0 {
+            bail!("Size must be at least 1");
+        }
+
+        options.size = options.size.min(self.max_size);
+
+        Ok(options)
+    }
+}
+
+// Holds one of the workers of Server::serve() until it is dropped, even by a panic
+struct Worker<'a>(&'a AtomicUsize);
+
+impl Drop for Worker<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Answers a connection no worker is free for without waiting on the client
+fn reject(stream: TcpStream) -> Result<()> {
+    // reading what already arrived lets the connection close without a reset
+    stream.set_nonblocking(true)?;
+
+    let mut request = [0; MAX_REQUEST as usize];
+    while let Ok(1..) = (&stream).read(&mut request) {}
+
+    stream.set_nonblocking(false)?;
+    stream.set_write_timeout(Some(REJECT_TIMEOUT))?;
+
+    let mut response = Response::error("503 Service Unavailable", "Too many requests");
+    response.headers.push(("Retry-After", String::from("1")));
+
+    response.write(&stream, false)?;
+
+    stream.shutdown(Shutdown::Write)?;
+
+    Ok(())
+}
+
+fn flag(name: &str, value: &str) -> Result<bool> {
+    match value {
+        "1" | "true" | "yes" | "" => Ok(true),
+        "0" | "false" | "no" => Ok(false),
+        _ => bail!("Invalid {} {}, expected 0 or 1", name, value),
+    }
+}
+
+impl Response {
+    fn error(status: &'static str, message: &str) -> Self {
+        Response {
+            status,
+            headers: vec![("Content-Type", String::from("text/plain; charset=utf-8"))],
+            body: format!("{}\n", message).into_bytes(),
+        }
+    }
+
+    fn write(&self, mut stream: &TcpStream, head: bool) -> Result<()> {
+        let mut response = format!("HTTP/1.1 {}\r\n", self.status);
+
+        for (name, value) in self.headers.iter() {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+
+        response.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+        response.push_str("Connection: close\r\n\r\n");
+
+        let mut response = response.into_bytes();
+
+        if !head {
+            response.extend_from_slice(&self.body);
+        }
+
+        stream
+            .write_all(&response)
+            .context("Unable to write response")?;
+
+        Ok(())
+    }
+}
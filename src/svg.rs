@@ -0,0 +1,281 @@
+use crate::render::BallProjection;
+use crate::scene::Background;
+use crate::Avatar;
+use crate::Color;
+use crate::RenderOptions;
+
+use std::f64::consts::PI;
+use std::fmt::Write;
+
+/// Draws +avatar+ as an SVG document.  The background is drawn from its gradients, rainbow
+/// arcs and clouds and each bone of the unicorn becomes a capsule filled with a gradient from
+/// the color of one end to the other, painted from the farthest bone to the nearest.
+///
+/// Coordinates are in pixels of an image +options+.size wide, a quadrant or region only moves
+/// the view box.
+pub(crate) fn render(avatar: &Avatar, options: &RenderOptions) -> String {
+    let size = options.size as f64;
+
+    let window = options.window();
+
+    let mut svg = String::new();
+
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">",
+        window.width, window.height, window.x, window.y, window.width, window.height
+    )
+    .unwrap();
+
+    if options.background {
+        background(&mut svg, &avatar.parameters().background, size);
+    }
+
+    unicorn(&mut svg, avatar, options.size);
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+fn background(svg: &mut String, background: &Background, size: f64) {
+    let fsize = size - 1.0;
+    let horizon = (size * background.horizon).floor();
+
+    let sky_a = Color::hsl(background.sky_hue, background.sky_sat, 60);
+    let sky_b = Color::hsl(background.sky_hue, background.sky_sat, 10);
+    let land_a = Color::hsl(
+        background.land_hue,
+        background.land_sat,
+        background.land_light,
+    );
+    let land_b = Color::hsl(
+        background.land_hue,
+        background.land_sat,
+        background.land_light / 2,
+    );
+
+    svg.push_str("<defs>\n");
+    gradient(svg, "sky", (0.0, 0.0), (0.0, fsize), sky_a, sky_b);
+    gradient(svg, "land", (0.0, 0.0), (fsize, 0.0), land_a, land_b);
+    writeln!(
+        svg,
+        "<clipPath id=\"above-horizon\"><rect width=\"{}\" height=\"{}\"/></clipPath>",
+        n(size),
+        n(horizon)
+    )
+    .unwrap();
+    svg.push_str("</defs>\n");
+
+    writeln!(
+        svg,
+        "<rect width=\"{}\" height=\"{}\" fill=\"url(#sky)\"/>",
+        n(size),
+        n(horizon)
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<rect y=\"{}\" width=\"{}\" height=\"{}\" fill=\"url(#land)\"/>",
+        n(horizon),
+        n(size),
+        n(size - horizon)
+    )
+    .unwrap();
+
+    let band_width = background.rainbow_band_width * fsize;
+    let center =
+        fsize * (background.rainbow_foot + background.rainbow_dir * background.rainbow_height);
+    let radius = (background.rainbow_height * fsize + 1.0).floor();
+
+    svg.push_str("<g clip-path=\"url(#above-horizon)\" fill=\"none\">\n");
+
+    for band in 0..7 {
+        writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+            n(center.round()),
+            n(horizon),
+            n(radius - (band as f64 + 0.5) * band_width),
+            hex(Color::hsl(band * 45, 100, 50)),
+            n(band_width)
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</g>\n");
+
+    for i in 0..background.cloud_positions.len() {
+        let position = &background.cloud_positions[i];
+        let cloud_size = &background.cloud_sizes[i];
+        let color = hex(Color::hsl(
+            background.sky_hue,
+            background.sky_sat,
+            background.cloud_lightnesses[i],
+        ));
+
+        let x = position.x * fsize + 0.5;
+        let y = position.y * fsize + 0.5;
+        let size1 = cloud_size.x * fsize;
+        let size2 = cloud_size.x * cloud_size.y * fsize;
+        let top = y - size1;
+
+        writeln!(svg, "<g fill=\"{}\">", color).unwrap();
+
+        for cx in [x - 2.0 * size1, x + 2.0 * size1].iter() {
+            writeln!(
+                svg,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\"/>",
+                n(*cx),
+                n(top),
+                n(size1)
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            svg,
+            "<path d=\"M{},{} A{},{} 0 0 1 {},{} Z\"/>",
+            n(x - size2),
+            n(top),
+            n(size2),
+            n(size2),
+            n(x + size2),
+            n(top)
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+            n(x - 2.0 * size1),
+            n(top),
+            n(4.0 * size1),
+            n(size1)
+        )
+        .unwrap();
+
+        svg.push_str("</g>\n");
+    }
+}
+
+fn unicorn(svg: &mut String, avatar: &Avatar, size: u32) {
+    let (world_view, scale, shift) = avatar.camera(size);
+
+    let mut bones: Vec<(BallProjection, BallProjection)> = avatar
+        .bones(&world_view)
+        .into_iter()
+        .filter(|(b1, b2)| b1.center_cs().z > 0.0 && b2.center_cs().z > 0.0)
+        .collect();
+
+    // painter's algorithm, the farthest bone first
+    bones.sort_by(|(a1, a2), (b1, b2)| (b1.z() + b2.z()).total_cmp(&(a1.z() + a2.z())));
+
+    // pixels are sampled at their top left corner
+    let to_image = |b: &BallProjection| {
+        (
+            b.x() * scale + shift.x + 0.5,
+            b.y() * scale + shift.y + 0.5,
+            b.projected_radius() * scale,
+        )
+    };
+
+    let mut gradients = String::new();
+    let mut paths = String::new();
+
+    for (i, (b1, b2)) in bones.iter().enumerate() {
+        let (x1, y1, r1) = to_image(b1);
+        let (x2, y2, r2) = to_image(b2);
+        let (c1, c2) = (b1.base.color, b2.base.color);
+
+        let fill = if c1 == c2 {
+            hex(c1)
+        } else {
+            let id = format!("bone{}", i);
+            gradient(&mut gradients, &id, (x1, y1), (x2, y2), c1, c2);
+
+            format!("url(#{})", id)
+        };
+
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        // one ball hides the other
+        if distance <= (r1 - r2).abs() {
+            let (x, y, r) = if r1 >= r2 { (x1, y1, r1) } else { (x2, y2, r2) };
+
+            writeln!(
+                paths,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+                n(x),
+                n(y),
+                n(r),
+                fill
+            )
+            .unwrap();
+
+            continue;
+        }
+
+        // the outer tangents touch both balls at +-alpha from the direction of the bone
+        let theta = dy.atan2(dx);
+        let alpha = ((r1 - r2) / distance).acos();
+
+        let point = |x: f64, y: f64, r: f64, angle: f64| {
+            format!("{},{}", n(x + r * angle.cos()), n(y + r * angle.sin()))
+        };
+
+        writeln!(
+            paths,
+            "<path d=\"M{} L{} A{},{} 0 {} 0 {} L{} A{},{} 0 {} 0 {} Z\" fill=\"{}\"/>",
+            point(x1, y1, r1, theta + alpha),
+            point(x2, y2, r2, theta + alpha),
+            n(r2),
+            n(r2),
+            (alpha > PI / 2.0) as u8,
+            point(x2, y2, r2, theta - alpha),
+            point(x1, y1, r1, theta - alpha),
+            n(r1),
+            n(r1),
+            (alpha < PI / 2.0) as u8,
+            point(x1, y1, r1, theta + alpha),
+            fill
+        )
+        .unwrap();
+    }
+
+    if !gradients.is_empty() {
+        writeln!(svg, "<defs>\n{}</defs>", gradients).unwrap();
+    }
+
+    svg.push_str(&paths);
+}
+
+fn gradient(svg: &mut String, id: &str, from: (f64, f64), to: (f64, f64), c1: Color, c2: Color) {
+    writeln!(
+        svg,
+        "<linearGradient id=\"{}\" gradientUnits=\"userSpaceOnUse\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"><stop offset=\"0\" stop-color=\"{}\"/><stop offset=\"1\" stop-color=\"{}\"/></linearGradient>",
+        id,
+        n(from.0),
+        n(from.1),
+        n(to.0),
+        n(to.1),
+        hex(c1),
+        hex(c2)
+    )
+    .unwrap();
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+// Formats +value+ with at most three decimals
+fn n(value: f64) -> String {
+    let s = format!("{:.3}", value);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+
+    match s {
+        "-0" => String::from("0"),
+        _ => s.to_string(),
+    }
+}
@@ -0,0 +1,207 @@
+//! Draws images in a terminal, for previewing avatars over SSH
+//!
+//!     use unicornify::terminal;
+//!     use unicornify::Avatar;
+//!     use unicornify::RenderOptions;
+//!
+//!     let avatar = Avatar::new("58479f76374a3ba3c69b9804163f39f4".to_string(), false).unwrap();
+//!     let image = avatar.render(&RenderOptions::new(32)).unwrap();
+//!
+//!     print!("{}", terminal::half_blocks(&image));
+
+use color_quant::NeuQuant;
+
+use image::Rgba;
+use image::RgbaImage;
+
+use std::fmt::Write;
+
+// Pixels less opaque than this are left to the terminal background
+const OPAQUE: u8 = 128;
+
+/// Draws +image+ with ANSI 24-bit colors and upper half block characters, so each character
+/// cell shows two pixels on top of each other.  Transparent pixels show the terminal background.
+pub fn half_blocks(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+
+    let mut ansi = String::new();
+
+    for y in (0..height).step_by(2) {
+        // only changed colors are sent
+        let mut current: (Option<Rgba<u8>>, Option<Rgba<u8>>) = (None, None);
+
+        for x in 0..width {
+            let top = visible(image.get_pixel(x, y));
+            let bottom = if y + 1 < height {
+                visible(image.get_pixel(x, y + 1))
+            } else {
+                None
+            };
+
+            // the glyph is drawn in the foreground color, the rest of the cell in the background
+            let (glyph, foreground, background) = match (top, bottom) {
+                (Some(t), b) => ('▀', Some(t), b),
+                (None, Some(b)) => ('▄', Some(b), None),
+                (None, None) => (' ', current.0, None),
+            };
+
+            if foreground != current.0 {
+                if let Some(c) = foreground {
+                    write!(ansi, "\x1b[38;2;{};{};{}m", c[0], c[1], c[2]).unwrap();
+                }
+            }
+
+            if background != current.1 {
+                match background {
+                    Some(c) => write!(ansi, "\x1b[48;2;{};{};{}m", c[0], c[1], c[2]).unwrap(),
+                    None => ansi.push_str("\x1b[49m"),
+                }
+            }
+
+            current = (foreground, background);
+
+            ansi.push(glyph);
+        }
+
+        ansi.push_str("\x1b[0m\n");
+    }
+
+    ansi
+}
+
+/// Draws +image+ as DEC sixel graphics in up to 256 colors chosen for the image.  Transparent
+/// pixels show the terminal background.
+pub fn sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+
+    // P2 = 1 leaves pixels that are not drawn transparent
+    let mut sixel = String::from("\x1bP0;1;0q");
+
+    write!(sixel, "\"1;1;{};{}", width, height).unwrap();
+
+    let mut opaque = Vec::new();
+
+    for pixel in image.pixels() {
+        if pixel[3] >= OPAQUE {
+            opaque.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 255]);
+        }
+    }
+
+    if opaque.is_empty() {
+        sixel.push_str("\x1b\\");
+
+        return sixel;
+    }
+
+    let palette = NeuQuant::new(10, 256, &opaque);
+
+    let indices: Vec<Option<usize>> = image
+        .pixels()
+        .map(|p| {
+            if p[3] >= OPAQUE {
+                Some(palette.index_of(&[p[0], p[1], p[2], 255]))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let map = palette.color_map_rgb();
+    let colors = map.len() / 3;
+
+    // only colors in the image are defined
+    let mut defined = vec![false; colors];
+
+    for i in indices.iter().flatten() {
+        if !defined[*i] {
+            defined[*i] = true;
+
+            write!(
+                sixel,
+                "#{};2;{};{};{}",
+                i,
+                percent(map[i * 3]),
+                percent(map[i * 3 + 1]),
+                percent(map[i * 3 + 2])
+            )
+            .unwrap();
+        }
+    }
+
+    // each band of six rows is drawn once per color in it, returning to its start with $
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+
+        let mut used = vec![false; colors];
+
+        for y in rows.clone() {
+            for x in 0..width {
+                if let Some(i) = indices[(y * width + x) as usize] {
+                    used[i] = true;
+                }
+            }
+        }
+
+        for color in (0..colors).filter(|&c| used[c]) {
+            write!(sixel, "#{}", color).unwrap();
+
+            let mut run = (0, '?');
+
+            for x in 0..width {
+                let mut bits = 0;
+
+                for y in rows.clone() {
+                    if indices[(y * width + x) as usize] == Some(color) {
+                        bits |= 1 << (y - band);
+                    }
+                }
+
+                let c = (63 + bits) as u8 as char;
+
+                if c != run.1 {
+                    repeat(&mut sixel, run);
+
+                    run = (0, c);
+                }
+
+                run.0 += 1;
+            }
+
+            // the rest of the row is empty
+            if run.1 != '?' {
+                repeat(&mut sixel, run);
+            }
+
+            sixel.push('$');
+        }
+
+        sixel.push('-');
+    }
+
+    sixel.push_str("\x1b\\");
+
+    sixel
+}
+
+fn visible(pixel: &Rgba<u8>) -> Option<Rgba<u8>> {
+    if pixel[3] >= OPAQUE {
+        Some(*pixel)
+    } else {
+        None
+    }
+}
+
+fn percent(channel: u8) -> u32 {
+    (channel as u32 * 100 + 127) / 255
+}
+
+// Writes +count+ copies of +c+, run length encoded when that is shorter
+fn repeat(sixel: &mut String, (count, c): (u32, char)) {
+    if count > 3 {
+        write!(sixel, "!{}{}", count, c).unwrap();
+    } else {
+        for _ in 0..count {
+            sixel.push(c);
+        }
+    }
+}
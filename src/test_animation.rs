@@ -0,0 +1,86 @@
+use crate::animation::FRAME_DELAY;
+use crate::test_fixtures::avatar;
+use crate::test_fixtures::temp_directory;
+use crate::unicorn::Pose;
+use crate::Animation;
+use crate::RenderOptions;
+
+use image::gif::GifDecoder;
+use image::AnimationDecoder;
+use image::RgbaImage;
+
+use std::fs;
+
+#[test]
+fn test_with_phase() {
+    let pose = Pose::Walk { phase: 0.75 };
+
+    assert_eq!(0.25, pose.with_phase(1.25).phase());
+    assert_eq!(0.5, pose.with_phase(-0.5).phase());
+}
+
+#[test]
+fn test_gait() {
+    let avatar = avatar();
+    let options = RenderOptions::new(32);
+
+    let gait = Animation::gait(&avatar, &options, 4, 2).unwrap();
+
+    assert_eq!(4, gait.frames.len());
+    assert_eq!(avatar.render(&options).unwrap(), gait.frames[0]);
+    assert_ne!(gait.frames[0], gait.frames[2]);
+
+    assert!(Animation::gait(&avatar, &options, 0, 1).is_err());
+}
+
+#[test]
+fn test_write_gif() {
+    let avatar = avatar();
+    let gait = Animation::gait(&avatar, &RenderOptions::new(16), 3, 1).unwrap();
+
+    let mut gif = Vec::new();
+    gait.write_gif(&mut gif).unwrap();
+
+    let frames = GifDecoder::new(gif.as_slice())
+        .unwrap()
+        .into_frames()
+        .collect_frames()
+        .unwrap();
+
+    assert_eq!(3, frames.len());
+    assert_eq!((40, 1), frames[0].delay().numer_denom_ms());
+}
+
+#[test]
+fn test_turntable() {
+    let avatar = avatar();
+    let options = RenderOptions::new(32);
+
+    let turntable = Animation::turntable(&avatar, &options, 4, 2).unwrap();
+
+    assert_eq!(4, turntable.frames.len());
+    assert_eq!(avatar.render(&options).unwrap(), turntable.frames[0]);
+    assert_ne!(turntable.frames[0], turntable.frames[2]);
+}
+
+#[test]
+fn test_save_frames() {
+    let directory = temp_directory("frames", "save");
+    fs::create_dir_all(&directory).unwrap();
+
+    let frame = RgbaImage::new(2, 2);
+    let animation = Animation::new(vec![frame; 11], FRAME_DELAY);
+
+    let template = directory.join("turn-{frame}.png");
+    let paths = animation.save_frames(template.to_str().unwrap()).unwrap();
+
+    let exists = paths.iter().all(|p| p.exists());
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert!(exists);
+    assert_eq!(directory.join("turn-00.png"), paths[0]);
+    assert_eq!(directory.join("turn-10.png"), paths[10]);
+
+    assert!(animation.save_frames("turn.png").is_err());
+}
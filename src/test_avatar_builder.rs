@@ -0,0 +1,133 @@
+use crate::test_fixtures::avatar;
+use crate::test_fixtures::HASH;
+use crate::unicorn::Pose;
+use crate::AvatarBuilder;
+use crate::RenderOptions;
+
+#[test]
+fn test_build_unchanged() {
+    let options = RenderOptions::new(32);
+
+    let expected = avatar();
+    let avatar = AvatarBuilder::new(HASH).build().unwrap();
+
+    assert_eq!(
+        expected.render(&options).unwrap(),
+        avatar.render(&options).unwrap()
+    );
+}
+
+#[test]
+fn test_body_hue() {
+    let expected = avatar();
+    let expected = expected.data();
+
+    let avatar = AvatarBuilder::new(HASH).body_hue(350).build().unwrap();
+    let data = avatar.data();
+
+    assert_eq!(350, data.body_hue);
+    assert_eq!(
+        (expected.horn_hue - expected.body_hue).rem_euclid(360),
+        (data.horn_hue - data.body_hue).rem_euclid(360)
+    );
+    assert_eq!(
+        (expected.hair_hue - expected.body_hue).rem_euclid(360),
+        (data.hair_hue - data.body_hue).rem_euclid(360)
+    );
+    assert_eq!(expected.body_sat, data.body_sat);
+    assert_eq!(expected.head_size, data.head_size);
+    assert_eq!(expected.y_angle, data.y_angle);
+}
+
+#[test]
+fn test_hair_count() {
+    let expected = avatar();
+    let expected = expected.data();
+    let count = expected.hair_count;
+
+    let more = AvatarBuilder::new(HASH)
+        .hair_count(count + 5)
+        .build()
+        .unwrap();
+    let more = more.data();
+
+    assert_eq!(count + 5, more.hair_count);
+    assert_eq!(count + 5, more.hair_starts.len());
+    assert_eq!(count + 5, more.hair_tip_lightnesses.len());
+    assert_eq!(expected.hair_starts[..], more.hair_starts[..count]);
+    assert_eq!(expected.tail_length, more.tail_length);
+
+    let fewer = AvatarBuilder::new(HASH).hair_count(3).build().unwrap();
+    let fewer = fewer.data();
+
+    assert_eq!(3, fewer.hair_count);
+    assert_eq!(expected.hair_lengths[..3], fewer.hair_lengths[..]);
+    assert_eq!(3, fewer.hair_straightnesses.len());
+}
+
+#[test]
+fn test_pose_and_camera() {
+    let options = RenderOptions::new(32);
+
+    let expected = avatar();
+
+    let avatar = AvatarBuilder::new(HASH)
+        .pose(Pose::Walk { phase: 0.25 })
+        .x_angle(10.0)
+        .y_angle(120.0)
+        .build()
+        .unwrap();
+
+    let data = avatar.data();
+
+    match data.pose {
+        Pose::Walk { phase } => assert_eq!(0.25, phase),
+        _ => panic!("expected a walk"),
+    }
+
+    assert!(data.neck_tilt <= 0.0);
+    assert_eq!(expected.data().horn_length, data.horn_length);
+    assert_ne!(
+        expected.render(&options).unwrap(),
+        avatar.render(&options).unwrap()
+    );
+}
+
+#[test]
+fn test_build_invalid() {
+    assert!(AvatarBuilder::new("xyz").build().is_err());
+    assert!(AvatarBuilder::new(HASH).body_hue(360).build().is_err());
+    assert!(AvatarBuilder::new(HASH).sky_hue(-1).build().is_err());
+    assert!(AvatarBuilder::new(HASH).horn_length(0.0).build().is_err());
+    assert!(AvatarBuilder::new(HASH).rainbow_dir(0.5).build().is_err());
+
+    let nan = std::f64::NAN;
+    let infinity = std::f64::INFINITY;
+
+    assert!(AvatarBuilder::new(HASH).horn_length(nan).build().is_err());
+    assert!(AvatarBuilder::new(HASH)
+        .horn_length(infinity)
+        .build()
+        .is_err());
+    assert!(AvatarBuilder::new(HASH).rainbow_foot(nan).build().is_err());
+    assert!(AvatarBuilder::new(HASH)
+        .rainbow_foot(-infinity)
+        .build()
+        .is_err());
+    assert!(AvatarBuilder::new(HASH)
+        .rainbow_height(nan)
+        .build()
+        .is_err());
+    assert!(AvatarBuilder::new(HASH)
+        .rainbow_band_width(infinity)
+        .build()
+        .is_err());
+    assert!(AvatarBuilder::new(HASH).x_angle(nan).build().is_err());
+
+    let e = AvatarBuilder::new(HASH)
+        .rainbow_foot(nan)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!("Rainbow foot must be positive, not NaN", e.to_string());
+}
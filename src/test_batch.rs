@@ -0,0 +1,85 @@
+use crate::batch::panic_error;
+use crate::test_fixtures::temp_directory;
+use crate::test_fixtures::HASH;
+use crate::Batch;
+use crate::HashAlgorithm;
+
+use std::fs;
+use std::panic;
+
+#[test]
+fn test_run() {
+    let directory = temp_directory("batch", "run");
+
+    let mut batch = Batch::new(&directory);
+    batch.sizes = vec![16, 24];
+    batch.template = String::from("{size}/{hash}.png");
+    batch.algorithm = HashAlgorithm::Sha256;
+    batch.threads = 2;
+
+    let input = format!("# users\n{}\n\nnot a hash\n Test@Example.com \n", HASH);
+
+    let summary = batch.run(input.as_bytes()).unwrap();
+
+    let email = "973dfe463ec85785f5f95af5ba3906eedb2d931c24e69824a89ea65dba4e813b";
+
+    let rendered = [
+        directory.join("16").join(format!("{}.png", HASH)),
+        directory.join("24").join(format!("{}.png", HASH)),
+        directory.join("16").join(format!("{}.png", email)),
+        directory.join("24").join(format!("{}.png", email)),
+    ];
+
+    let exists: Vec<bool> = rendered.iter().map(|p| p.exists()).collect();
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!([true; 4], exists[..]);
+
+    assert_eq!(3, summary.entries);
+    assert_eq!(4, summary.images);
+    assert_eq!(1, summary.failures.len());
+    assert_eq!(4, summary.failures[0].line);
+    assert_eq!("not a hash", summary.failures[0].input);
+    assert_eq!("3 entries, 4 images written, 1 failed", summary.to_string());
+}
+
+#[test]
+fn test_validate_template() {
+    let mut batch = Batch::new(temp_directory("batch", "validate"));
+    batch.sizes = vec![16, 32];
+
+    assert!(batch.validate().is_ok());
+
+    batch.template = String::from("{hash}.png");
+    assert!(batch.validate().is_err());
+
+    batch.template = String::from("{size}.png");
+    assert!(batch.validate().is_err());
+
+    batch.template = String::from("{hash}-{size}-{user}.png");
+    assert!(batch.validate().is_err());
+
+    batch.template = String::from("{hash}-{size}");
+    assert!(batch.validate().is_err());
+
+    batch.template = String::from("{line}-{size}.jpg");
+    assert!(batch.validate().is_ok());
+
+    assert_eq!("7-16.jpg", batch.file_name(HASH, 16, 7));
+}
+
+#[test]
+fn test_panic_error() {
+    let payload = panic::catch_unwind(|| panic!("bad {}", "entry")).unwrap_err();
+    assert_eq!(
+        "Rendering panicked: bad entry",
+        panic_error(payload).to_string()
+    );
+
+    let payload = panic::catch_unwind(|| panic!("static")).unwrap_err();
+    assert_eq!(
+        "Rendering panicked: static",
+        panic_error(payload).to_string()
+    );
+}
@@ -0,0 +1,126 @@
+use crate::geometry::Ball;
+use crate::geometry::Vector;
+use crate::render::BallProjection;
+use crate::render::BoneTracer;
+use crate::render::TraceResult;
+use crate::render::WorldView;
+use crate::render::PACKET_SIZE;
+use crate::Color;
+
+fn world_view() -> WorldView {
+    let camera = Vector::new(60.0, 40.0, -900.0);
+    let look_at = Vector::new(100.0, 120.0, 0.0);
+
+    WorldView::new(camera, look_at, 300.0)
+}
+
+fn bone_tracer(b1: Ball, b2: Ball) -> BoneTracer {
+    let world_view = world_view();
+
+    BoneTracer::new(
+        BallProjection::new(&world_view, b1),
+        BallProjection::new(&world_view, b2),
+    )
+}
+
+// Compares bits so NaN and signed zero differences are caught too
+fn bits(result: TraceResult) -> Option<(u64, u64, u64, u64, Color)> {
+    result.map(|(z, dir, color)| {
+        (
+            z.to_bits(),
+            dir.x.to_bits(),
+            dir.y.to_bits(),
+            dir.z.to_bits(),
+            color,
+        )
+    })
+}
+
+fn assert_packet_matches_scalar(tracer: &BoneTracer) {
+    let world_view = world_view();
+    let mut hits = 0;
+
+    for y in -100..100 {
+        for x in (-100..100).step_by(PACKET_SIZE) {
+            let mut rays = [Vector::zero(); PACKET_SIZE];
+
+            for (i, ray) in rays.iter_mut().enumerate() {
+                *ray = world_view.ray((x + i as i32) as f64, y as f64);
+            }
+
+            let packet = tracer.trace_packet(&rays);
+
+            for (i, ray) in rays.iter().enumerate() {
+                let fx = (x + i as i32) as f64;
+                let scalar = tracer.trace(fx, y as f64, *ray);
+
+                if scalar.is_some() {
+                    hits += 1;
+                }
+
+                assert_eq!(bits(packet[i]), bits(scalar), "pixel {} {}", fx, y);
+            }
+        }
+    }
+
+    assert!(hits > 0, "bone not visible, test is meaningless");
+}
+
+#[test]
+fn test_trace_packet_tapered() {
+    let b1 = Ball::new("b1".into(), 80.0, 120.0, 0.0, 40.0, Color::rgb(200, 0, 0));
+    let b2 = Ball::new("b2".into(), 140.0, 150.0, 30.0, 12.0, Color::rgb(0, 0, 200));
+
+    assert_packet_matches_scalar(&bone_tracer(b1.clone(), b2.clone()));
+    assert_packet_matches_scalar(&bone_tracer(b2, b1));
+}
+
+#[test]
+fn test_trace_packet_cylinder() {
+    let b1 = Ball::new("b1".into(), 60.0, 100.0, 0.0, 20.0, Color::rgb(0, 200, 0));
+    let b2 = Ball::new("b2".into(), 160.0, 100.0, -20.0, 20.0, Color::white());
+
+    assert_packet_matches_scalar(&bone_tracer(b1, b2));
+}
+
+#[test]
+fn test_trace_packet_ball() {
+    // a single ball is traced as a bone from itself to itself
+    let ball = Ball::new(
+        "ball".into(),
+        100.0,
+        120.0,
+        0.0,
+        30.0,
+        Color::rgb(0, 100, 200),
+    );
+
+    assert_packet_matches_scalar(&bone_tracer(ball.clone(), ball));
+}
+
+#[test]
+fn test_trace_packet_steep() {
+    // a small ball barely outside a large one exercises the cone tip fallbacks
+    let b1 = Ball::new("b1".into(), 100.0, 120.0, 0.0, 50.0, Color::rgb(10, 20, 30));
+    let b2 = Ball::new("b2".into(), 145.0, 120.0, 5.0, 10.0, Color::rgb(30, 20, 10));
+
+    assert_packet_matches_scalar(&bone_tracer(b1, b2));
+}
+
+#[test]
+fn test_trace_packet_nan() {
+    let b1 = Ball::new("b1".into(), 80.0, 120.0, 0.0, 40.0, Color::rgb(200, 0, 0));
+    let b2 = Ball::new("b2".into(), 140.0, 150.0, 30.0, 12.0, Color::rgb(0, 0, 200));
+    let tracer = bone_tracer(b1, b2);
+
+    // a NaN discriminant is not below zero, so both paths treat it alike
+    let ray = Vector::new(std::f64::NAN, 0.0, 1.0);
+    let rays = [ray; PACKET_SIZE];
+
+    let packet = tracer.trace_packet(&rays);
+    let scalar = tracer.trace(0.0, 0.0, ray);
+
+    for result in packet.iter() {
+        assert_eq!(bits(*result), bits(scalar));
+    }
+}
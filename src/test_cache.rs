@@ -0,0 +1,150 @@
+use crate::test_fixtures::temp_directory;
+use crate::test_fixtures::HASH;
+use crate::Avatar;
+use crate::AvatarCache;
+use crate::RenderOptions;
+use crate::RENDER_VERSION;
+
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_avatar_is_send_sync() {
+    assert_send_sync::<Avatar>();
+    assert_send_sync::<AvatarCache>();
+}
+
+#[test]
+fn test_avatar_cached() {
+    let cache = AvatarCache::new(2);
+
+    let a = cache.avatar(HASH, false).unwrap();
+    let b = cache.avatar(HASH, false).unwrap();
+    let zoomed = cache.avatar(HASH, true).unwrap();
+
+    assert!(Arc::ptr_eq(&a, &b));
+    assert!(!Arc::ptr_eq(&a, &zoomed));
+
+    let upper = cache.avatar(&HASH.to_ascii_uppercase(), false).unwrap();
+
+    assert!(Arc::ptr_eq(&a, &upper));
+}
+
+#[test]
+fn test_avatar_evicted() {
+    let cache = AvatarCache::new(2);
+
+    let a = cache.avatar("1", false).unwrap();
+    let b = cache.avatar("2", false).unwrap();
+
+    // touch 1 so 2 is the least recently used
+    assert!(Arc::ptr_eq(&a, &cache.avatar("1", false).unwrap()));
+
+    cache.avatar("3", false).unwrap();
+
+    assert!(Arc::ptr_eq(&a, &cache.avatar("1", false).unwrap()));
+    assert!(!Arc::ptr_eq(&b, &cache.avatar("2", false).unwrap()));
+}
+
+#[test]
+fn test_image_shared_between_threads() {
+    let cache = Arc::new(AvatarCache::new(4));
+    let options = RenderOptions::new(16);
+
+    let first = cache.image(HASH, &options).unwrap();
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let cache = cache.clone();
+            let options = options.clone();
+
+            thread::spawn(move || cache.image(HASH, &options).unwrap())
+        })
+        .collect();
+
+    for thread in threads {
+        assert!(Arc::ptr_eq(&first, &thread.join().unwrap()));
+    }
+
+    assert_eq!(&first[1..4], b"PNG");
+}
+
+#[test]
+fn test_image_directory() {
+    let directory = temp_directory("cache", "image");
+    let options = RenderOptions::new(16);
+
+    let cache = AvatarCache::with_directory(1, &directory).unwrap();
+    let image = cache.image(HASH, &options).unwrap();
+
+    let files: Vec<String> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+
+    let expected = format!(
+        "v{}-{}-{}-16-0-b---.png",
+        RENDER_VERSION,
+        env!("CARGO_PKG_VERSION"),
+        HASH
+    );
+    assert_eq!(vec![expected], files);
+
+    let cache = AvatarCache::with_directory(1, &directory).unwrap();
+    let reread = cache.image(HASH, &options).unwrap();
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(image, reread);
+}
+
+#[test]
+fn test_image_directory_truncated() {
+    let directory = temp_directory("cache", "truncated");
+    let options = RenderOptions::new(16);
+
+    let cache = AvatarCache::with_directory(1, &directory).unwrap();
+    let image = cache.image(HASH, &options).unwrap();
+
+    let path = fs::read_dir(&directory)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+
+    // a write cut short
+    fs::write(&path, &image[..image.len() / 2]).unwrap();
+
+    let cache = AvatarCache::with_directory(1, &directory).unwrap();
+    let reread = cache.image(HASH, &options).unwrap();
+
+    let rewritten = fs::read(&path).unwrap();
+    let files = fs::read_dir(&directory).unwrap().count();
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(image, reread);
+    assert_eq!(image.as_slice(), rewritten.as_slice());
+    // no temporary file is left behind
+    assert_eq!(1, files);
+}
+
+#[test]
+fn test_image_invalid_hash() {
+    let cache = AvatarCache::new(1);
+
+    assert!(cache.image("../etc", &RenderOptions::new(16)).is_err());
+}
+
+#[test]
+fn test_image_unsupported_options() {
+    let cache = AvatarCache::new(1);
+    let mut options = RenderOptions::new(16);
+    options.shading = true;
+
+    assert!(cache.image(HASH, &options).is_err());
+}
@@ -0,0 +1,29 @@
+use crate::Avatar;
+use crate::RenderOptions;
+
+use image::RgbaImage;
+
+use std::path::PathBuf;
+
+// The hash most tests render
+pub const HASH: &str = "58479f76374a3ba3c69b9804163f39f4";
+
+// The avatar of HASH
+pub fn avatar() -> Avatar {
+    Avatar::new(HASH.to_string(), false).unwrap()
+}
+
+// The avatar of HASH rendered at +size+ with the default options
+pub fn avatar_image(size: u32) -> RgbaImage {
+    avatar().render(&RenderOptions::new(size)).unwrap()
+}
+
+// A directory for the +name+ test of +kind+ no other test or test run writes to
+pub fn temp_directory(kind: &str, name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "unicornify-{}-{}-{}",
+        kind,
+        name,
+        std::process::id()
+    ))
+}
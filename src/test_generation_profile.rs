@@ -0,0 +1,145 @@
+use crate::generation_profile::choose;
+use crate::unicorn::Pose;
+use crate::Avatar;
+use crate::AvatarBuilder;
+use crate::FloatRange;
+use crate::GenerationProfile;
+use crate::IntRange;
+use crate::Random;
+use crate::RenderOptions;
+
+const HASHES: [&str; 4] = [
+    "58479f76374a3ba3c69b9804163f39f4",
+    "0bc83cb571cd1c50ba6f3e8a78ef1346",
+    "ffffffffffffffffffffffffffffffff",
+    "84059b07d4be67b806386c0aad8070a23f18836bbaae342275dc0a83414c32ee",
+];
+
+#[test]
+fn test_default_profile_unchanged() {
+    let options = RenderOptions::new(32);
+
+    for hash in HASHES.iter() {
+        let expected = Avatar::new(String::from(*hash), false).unwrap();
+        let avatar = AvatarBuilder::new(*hash)
+            .profile(GenerationProfile::default())
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            expected.render(&options).unwrap(),
+            avatar.render(&options).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_always_walking() {
+    let profile = GenerationProfile {
+        pose: [0.0, 1.0],
+        ..GenerationProfile::default()
+    };
+
+    for hash in HASHES.iter() {
+        let avatar = AvatarBuilder::new(*hash)
+            .profile(profile.clone())
+            .build()
+            .unwrap();
+
+        match avatar.data().pose {
+            Pose::Walk { .. } => (),
+            _ => panic!("{} is not walking", hash),
+        }
+    }
+}
+
+#[test]
+fn test_ranges() {
+    let profile = GenerationProfile {
+        horn_length: IntRange::new(20, 30),
+        body_sat: IntRange::new(20, 35),
+        brow_length: FloatRange::between(1.0, 1.5),
+        ..GenerationProfile::default()
+    };
+
+    for hash in HASHES.iter() {
+        let builder = AvatarBuilder::new(*hash).profile(profile.clone());
+
+        let avatar = builder.build().unwrap();
+        let data = avatar.data();
+
+        assert!((20.0..=30.0).contains(&data.horn_length));
+        assert!((20..=35).contains(&data.body_sat));
+        assert!((1.0..=1.5).contains(&data.brow_length));
+
+        let again = builder.build().unwrap();
+
+        assert_eq!(data.horn_length, again.data().horn_length);
+        assert_eq!(data.hair_starts, again.data().hair_starts);
+    }
+}
+
+#[test]
+fn test_choose() {
+    let mut expected = Random::new();
+    expected.seed_u32(1234);
+
+    let mut rand = Random::new();
+    rand.seed_u32(1234);
+
+    for _ in 0..100 {
+        assert_eq!(
+            expected.choice(3) as usize,
+            choose(&mut rand, &[2.0, 2.0, 2.0])
+        );
+    }
+
+    for _ in 0..100 {
+        assert_eq!(1, choose(&mut rand, &[0.0, 1.0, 0.0]));
+    }
+}
+
+#[test]
+fn test_validate() {
+    assert!(GenerationProfile::default().validate().is_ok());
+
+    let inverted = GenerationProfile {
+        head_size: IntRange::new(40, 25),
+        ..GenerationProfile::default()
+    };
+    assert!(inverted.validate().is_err());
+
+    let negative = GenerationProfile {
+        tail_gamma: FloatRange::new(0.1, -1.0),
+        ..GenerationProfile::default()
+    };
+    assert!(negative.validate().is_err());
+
+    let low_horizon = GenerationProfile {
+        horizon: FloatRange::new(0.2, 0.1),
+        ..GenerationProfile::default()
+    };
+    assert!(low_horizon.validate().is_err());
+
+    let hue = GenerationProfile {
+        sky_hue: IntRange::new(0, 400),
+        ..GenerationProfile::default()
+    };
+    assert!(hue.validate().is_err());
+
+    let flat_rainbow = GenerationProfile {
+        rainbow_height: FloatRange::new(0.0, 1.0),
+        ..GenerationProfile::default()
+    };
+    assert!(flat_rainbow.validate().is_err());
+
+    let no_pose = GenerationProfile {
+        pose: [0.0, 0.0],
+        ..GenerationProfile::default()
+    };
+    assert!(no_pose.validate().is_err());
+    assert!(AvatarBuilder::new(HASHES[0])
+        .profile(no_pose)
+        .build()
+        .is_err());
+}
@@ -0,0 +1,160 @@
+use crate::test_fixtures::avatar;
+use crate::test_fixtures::temp_directory;
+use crate::test_fixtures::HASH;
+use crate::Avatar;
+use crate::IconSet;
+use crate::Provenance;
+use crate::RenderOptions;
+use crate::ICON_SIZES;
+
+use image::ImageFormat;
+
+use std::fs;
+
+#[test]
+fn test_new() {
+    let icons = IconSet::new("icons");
+
+    assert_eq!(ICON_SIZES.to_vec(), icons.sizes);
+    assert!(icons.validate().is_ok());
+}
+
+#[test]
+fn test_validate() {
+    let mut icons = IconSet::new("icons");
+    icons.sizes = vec![16, 32, 16];
+
+    let e = icons.validate().unwrap_err();
+    assert_eq!("Icon size 16 is given twice", e.to_string());
+
+    icons.sizes = vec![16];
+    icons.options.quadrant = Some(1);
+
+    assert!(icons.validate().is_err());
+}
+
+#[test]
+fn test_render() {
+    let mut icons = IconSet::new("icons");
+    icons.sizes = vec![16, 48];
+    icons.threads = 2;
+
+    let images = icons.render(&avatar()).unwrap();
+
+    let dimensions: Vec<(u32, u32)> = images.iter().map(|i| i.dimensions()).collect();
+    assert_eq!(vec![(16, 16), (48, 48)], dimensions);
+
+    // rendered directly, not scaled from a bigger icon
+    let direct = avatar().render(&RenderOptions::new(16)).unwrap();
+    assert_eq!(direct, images[0]);
+}
+
+#[test]
+fn test_ico() {
+    let images: Vec<_> = [16, 32, 256]
+        .iter()
+        .map(|s| avatar().render(&RenderOptions::new(*s)).unwrap())
+        .collect();
+
+    let ico = IconSet::ico(&images).unwrap();
+
+    assert_eq!([0, 0, 1, 0, 3, 0], ico[..6]);
+
+    let widths: Vec<u8> = (0..3).map(|i| ico[6 + 16 * i]).collect();
+    assert_eq!(vec![16, 32, 0], widths);
+
+    // the decoder picks the biggest image
+    let decoded = image::load_from_memory_with_format(&ico, ImageFormat::Ico).unwrap();
+    assert_eq!(images[2], decoded.into_rgba8());
+}
+
+#[test]
+fn test_ico_too_big() {
+    let image = avatar().render(&RenderOptions::new(257)).unwrap();
+
+    let e = IconSet::ico(&[image]).unwrap_err();
+    assert_eq!(
+        "Icons are at most 256 pixels wide and high, not 257x257",
+        e.to_string()
+    );
+}
+
+#[test]
+fn test_manifest() {
+    let mut icons = IconSet::new("icons");
+    icons.sizes = vec![32, 512];
+
+    let expected = concat!(
+        "{\n",
+        "  \"icons\": [\n",
+        "    { \"src\": \"icon-32.png\", \"sizes\": \"32x32\", \"type\": \"image/png\" },\n",
+        "    { \"src\": \"icon-512.png\", \"sizes\": \"512x512\", \"type\": \"image/png\" },\n",
+        "    { \"src\": \"favicon.ico\", \"sizes\": \"32x32\", \"type\": \"image/x-icon\" }\n",
+        "  ]\n",
+        "}\n"
+    );
+
+    assert_eq!(expected, icons.manifest());
+}
+
+#[test]
+fn test_write() {
+    let directory = temp_directory("icons", "write");
+
+    let mut icons = IconSet::new(&directory);
+    icons.sizes = vec![16, 300];
+
+    let written = icons.write(&avatar()).unwrap();
+
+    let names: Vec<String> = written
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+
+    let exists: Vec<bool> = written.iter().map(|p| p.exists()).collect();
+    let png = image::open(directory.join("icon-300.png"))
+        .unwrap()
+        .into_rgba8();
+    let ico = fs::read(directory.join("favicon.ico")).unwrap();
+    let provenance = Provenance::read(&fs::read(directory.join("icon-16.png")).unwrap()).unwrap();
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(
+        vec![
+            "icon-16.png",
+            "icon-300.png",
+            "favicon.ico",
+            "manifest.json"
+        ],
+        names
+    );
+    assert_eq!([true; 4], exists[..]);
+    assert_eq!((300, 300), png.dimensions());
+    // only the 16 pixel icon fits in the ICO
+    assert_eq!([1, 0], ico[4..6]);
+    // the hash comes from the avatar
+    assert_eq!(HASH, provenance.hash);
+    assert_eq!(16, provenance.options.size);
+}
+
+#[test]
+fn test_write_without_hash() {
+    let directory = temp_directory("icons", "write-without-hash");
+
+    let mut icons = IconSet::new(&directory);
+    icons.sizes = vec![16];
+
+    // the same unicorn, but not made from its hash
+    let anonymous = Avatar::from_parameters(avatar().parameters().clone()).unwrap();
+    assert_eq!(None, anonymous.hash());
+
+    icons.write(&anonymous).unwrap();
+
+    let png = fs::read(directory.join("icon-16.png")).unwrap();
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert!(Provenance::read(&png).is_err());
+    assert_eq!(Some(HASH), avatar().hash());
+}
@@ -0,0 +1,47 @@
+use crate::identity::email_hash;
+use crate::identity::identity_hash;
+use crate::identity::parse_hash;
+use crate::HashAlgorithm;
+
+const MD5: &str = "0bc83cb571cd1c50ba6f3e8a78ef1346";
+const SHA256: &str = "84059b07d4be67b806386c0aad8070a23f18836bbaae342275dc0a83414c32ee";
+
+#[test]
+fn test_email_hash() {
+    assert_eq!(
+        MD5,
+        email_hash(" MyEmailAddress@example.com ", HashAlgorithm::Md5)
+    );
+    assert_eq!(
+        SHA256,
+        email_hash("MyEmailAddress@example.com\n", HashAlgorithm::Sha256)
+    );
+}
+
+#[test]
+fn test_hash_algorithm_from_str() {
+    assert_eq!(HashAlgorithm::Md5, "md5".parse().unwrap());
+    assert_eq!(HashAlgorithm::Sha256, "SHA-256".parse().unwrap());
+    assert!("sha1".parse::<HashAlgorithm>().is_err());
+}
+
+#[test]
+fn test_parse_hash() {
+    assert_eq!(MD5, parse_hash(&MD5.to_uppercase()).unwrap());
+    assert_eq!(SHA256, parse_hash(SHA256).unwrap());
+
+    assert!(parse_hash("").is_err());
+    assert!(parse_hash(&MD5[1..]).is_err());
+    assert!(parse_hash(&format!("{}0", SHA256)).is_err());
+    assert!(parse_hash(&MD5.replace('b', "g")).is_err());
+}
+
+#[test]
+fn test_identity_hash() {
+    assert_eq!(
+        MD5,
+        identity_hash("MyEmailAddress@example.com", HashAlgorithm::Md5).unwrap()
+    );
+    assert_eq!(MD5, identity_hash(MD5, HashAlgorithm::Sha256).unwrap());
+    assert!(identity_hash("someone", HashAlgorithm::Md5).is_err());
+}
@@ -0,0 +1,106 @@
+use crate::geometry::Ball;
+use crate::geometry::Vector;
+use crate::test_fixtures::avatar;
+use crate::Color;
+use crate::Mesh;
+
+fn ball(x: f64, y: f64, z: f64, radius: f64) -> Ball {
+    Ball::new("ball".into(), x, y, z, radius, Color::white())
+}
+
+// Every triangle faces away from +center+
+fn assert_outward(mesh: &Mesh, center: Vector) {
+    for t in mesh.indices.chunks(3) {
+        let a = mesh.positions[t[0] as usize];
+        let b = mesh.positions[t[1] as usize];
+        let c = mesh.positions[t[2] as usize];
+
+        let (u, v) = (b - a, c - a);
+        let normal = Vector::new(
+            u.y * v.z - u.z * v.y,
+            u.z * v.x - u.x * v.z,
+            u.x * v.y - u.y * v.x,
+        );
+
+        // triangles at the poles have no area
+        if normal.length() < 1e-9 {
+            continue;
+        }
+
+        assert!(normal.scalar_product(a - center) > 0.0);
+    }
+}
+
+#[test]
+fn test_add_bone() {
+    let ends = [
+        Vector::new(30.0, 40.0, 0.0),
+        Vector::new(-30.0, 0.0, 40.0),
+        Vector::new(0.0, -50.0, 0.0),
+        Vector::new(10.0, 20.0, -30.0),
+    ];
+
+    for end in ends.iter() {
+        let mut mesh = Mesh::new();
+
+        mesh.add_bone(&ball(0.0, 0.0, 0.0, 10.0), &ball(end.x, end.y, end.z, 5.0));
+
+        assert_eq!(mesh.positions.len(), mesh.normals.len());
+        assert_eq!(mesh.positions.len(), mesh.colors.len());
+        assert!(mesh.triangles() > 0);
+
+        for (p, n) in mesh.positions.iter().zip(mesh.normals.iter()) {
+            assert!((n.length() - 1.0).abs() < 1e-9);
+
+            let to_b1 = p.length();
+            let to_b2 = (*p - *end).length();
+
+            assert!((to_b1 - 10.0).abs() < 1e-9 || (to_b2 - 5.0).abs() < 1e-9);
+        }
+
+        assert_outward(&mesh, *end * 0.5);
+    }
+}
+
+#[test]
+fn test_add_bone_ball() {
+    let mut mesh = Mesh::new();
+
+    let b = ball(1.0, 2.0, 3.0, 4.0);
+
+    mesh.add_bone(&b, &b);
+
+    for p in mesh.positions.iter() {
+        assert!(((*p - Vector::new(1.0, 2.0, 3.0)).length() - 4.0).abs() < 1e-9);
+    }
+
+    assert_outward(&mesh, Vector::new(1.0, 2.0, 3.0));
+}
+
+#[test]
+fn test_write_glb() {
+    let avatar = avatar();
+
+    let mesh = avatar.mesh();
+
+    let mut glb = Vec::new();
+    mesh.write_glb(&mut glb).unwrap();
+
+    let u32_at = |i: usize| u32::from_le_bytes([glb[i], glb[i + 1], glb[i + 2], glb[i + 3]]);
+
+    assert_eq!(b"glTF", &glb[0..4]);
+    assert_eq!(2, u32_at(4));
+    assert_eq!(glb.len(), u32_at(8) as usize);
+
+    let json_length = u32_at(12) as usize;
+    assert_eq!(b"JSON", &glb[16..20]);
+
+    let json = std::str::from_utf8(&glb[20..20 + json_length]).unwrap();
+    assert!(json.contains(&format!("\"count\":{}", mesh.indices.len())));
+
+    let bin = 20 + json_length;
+    assert_eq!(b"BIN\0", &glb[bin + 4..bin + 8]);
+    assert_eq!(glb.len(), bin + 8 + u32_at(bin) as usize);
+
+    assert!(Mesh::new().write_glb(Vec::new()).is_err());
+}
@@ -0,0 +1,200 @@
+use crate::test_fixtures::avatar;
+use crate::test_fixtures::avatar_image;
+use crate::OutputFormat;
+use crate::RenderOptions;
+use crate::JPEG_QUALITY;
+
+use image::ImageFormat;
+use image::Rgba;
+use image::RgbaImage;
+
+use image_webp::WebPDecoder;
+
+use std::io::Cursor;
+
+// Decodes +webp+ back to RGBA
+fn decode_webp(webp: &[u8]) -> RgbaImage {
+    let mut decoder = WebPDecoder::new(Cursor::new(webp)).unwrap();
+    let (width, height) = decoder.dimensions();
+
+    let mut buffer = vec![0; decoder.output_buffer_size().unwrap()];
+    decoder.read_image(&mut buffer).unwrap();
+
+    // images without alpha are decoded to RGB
+    let rgba = if decoder.has_alpha() {
+        buffer
+    } else {
+        buffer
+            .chunks(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect()
+    };
+
+    RgbaImage::from_raw(width, height, rgba).unwrap()
+}
+
+#[test]
+fn test_from_path() {
+    assert_eq!(
+        OutputFormat::Png,
+        OutputFormat::from_path("a/b.png").unwrap()
+    );
+    assert_eq!(
+        OutputFormat::Jpeg(JPEG_QUALITY),
+        OutputFormat::from_path("b.JPG").unwrap()
+    );
+    assert_eq!(
+        OutputFormat::WebP,
+        OutputFormat::from_path("b.webp").unwrap()
+    );
+    assert_eq!(
+        OutputFormat::Rgba,
+        OutputFormat::from_path("b.raw").unwrap()
+    );
+
+    assert!(OutputFormat::from_path("b").is_err());
+    assert!(OutputFormat::from_path("b.tiff").is_err());
+
+    assert_eq!(OutputFormat::Ico, "ICO".parse().unwrap());
+}
+
+#[test]
+fn test_extension() {
+    for format in [
+        OutputFormat::Png,
+        OutputFormat::Jpeg(JPEG_QUALITY),
+        OutputFormat::Bmp,
+        OutputFormat::Gif,
+        OutputFormat::Ico,
+        OutputFormat::WebP,
+        OutputFormat::Rgba,
+    ]
+    .iter()
+    {
+        let path = format!("out.{}", format.extension());
+
+        assert_eq!(*format, OutputFormat::from_path(&path).unwrap());
+    }
+
+    assert_eq!("jpg", OutputFormat::Jpeg(50).extension());
+}
+
+#[test]
+fn test_encode() {
+    let image = avatar_image(32);
+
+    for (format, image_format) in [
+        (OutputFormat::Png, ImageFormat::Png),
+        (OutputFormat::Bmp, ImageFormat::Bmp),
+        (OutputFormat::Ico, ImageFormat::Ico),
+        (OutputFormat::Jpeg(50), ImageFormat::Jpeg),
+    ]
+    .iter()
+    {
+        let encoded = format.encode(&image).unwrap();
+
+        let decoded = image::load_from_memory_with_format(&encoded, *image_format)
+            .unwrap()
+            .to_rgba8();
+
+        assert_eq!(image.dimensions(), decoded.dimensions());
+
+        if *format != OutputFormat::Jpeg(50) {
+            assert_eq!(image, decoded);
+        }
+    }
+
+    assert_eq!(image.as_raw(), &OutputFormat::Rgba.encode(&image).unwrap());
+
+    assert!(OutputFormat::Jpeg(0).encode(&image).is_err());
+    assert!(OutputFormat::Ico.encode(&avatar_image(257)).is_err());
+}
+
+#[test]
+fn test_encode_jpeg_alpha() {
+    // stored colors under transparent pixels must not show
+    let mut image = RgbaImage::from_pixel(16, 16, Rgba([240, 240, 240, 0]));
+
+    for x in 8..16 {
+        for y in 0..16 {
+            image.put_pixel(x, y, Rgba([240, 240, 240, 128]));
+        }
+    }
+
+    let jpeg = OutputFormat::Jpeg(100).encode(&image).unwrap();
+
+    let decoded = image::load_from_memory_with_format(&jpeg, ImageFormat::Jpeg)
+        .unwrap()
+        .to_rgb8();
+
+    let transparent = decoded.get_pixel(2, 8);
+    let half = decoded.get_pixel(13, 8);
+
+    assert!(transparent.0.iter().all(|c| *c < 8), "{:?}", transparent);
+    assert!(half.0.iter().all(|c| (112..=128).contains(c)), "{:?}", half);
+}
+
+#[test]
+fn test_encode_webp() {
+    let webp = OutputFormat::WebP.encode(&avatar_image(100)).unwrap();
+
+    let u32_at = |i: usize| u32::from_le_bytes([webp[i], webp[i + 1], webp[i + 2], webp[i + 3]]);
+
+    assert_eq!(b"RIFF", &webp[0..4]);
+    assert_eq!(webp.len(), u32_at(4) as usize + 8);
+    assert_eq!(b"WEBPVP8L", &webp[8..16]);
+    assert_eq!(0, webp.len() % 2);
+
+    // signature, then 14 bits each of width - 1 and height - 1 and the alpha hint
+    assert_eq!(0x2f, webp[20]);
+    assert_eq!(99, u32_at(21) & 0x3fff);
+    assert_eq!(99, (u32_at(21) >> 14) & 0x3fff);
+    assert_eq!(0, (u32_at(21) >> 28) & 1);
+
+    let mut transparent = RgbaImage::new(3, 2);
+    transparent.put_pixel(1, 1, Rgba([1, 2, 3, 4]));
+
+    let webp = OutputFormat::WebP.encode(&transparent).unwrap();
+    assert_eq!(1, (webp[24] >> 4) & 1);
+
+    assert!(OutputFormat::WebP.encode(&RgbaImage::new(0, 0)).is_err());
+}
+
+#[test]
+fn test_encode_webp_round_trip() {
+    let avatar = avatar();
+
+    let mut transparent = RenderOptions::new(33);
+    transparent.background = false;
+
+    // a repeatable pseudo random image that LZ77 and the predictors cannot shrink
+    let mut seed: u32 = 1;
+    let noise = RgbaImage::from_fn(17, 17, |_, _| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+
+        let [r, g, b, a] = seed.to_le_bytes();
+        Rgba([r, g, b, a])
+    });
+
+    let images = [
+        RgbaImage::from_pixel(1, 1, Rgba([1, 2, 3, 255])),
+        avatar_image(100),
+        avatar_image(128),
+        avatar.render(&transparent).unwrap(),
+        noise,
+        RgbaImage::from_fn(5000, 2, |x, y| {
+            Rgba([x as u8, (x >> 8) as u8, y as u8, 255])
+        }),
+    ];
+
+    for image in images.iter() {
+        let webp = OutputFormat::WebP.encode(image).unwrap();
+
+        assert!(
+            *image == decode_webp(&webp),
+            "{}x{} did not round trip",
+            image.width(),
+            image.height()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::test_fixtures::avatar;
+use crate::Avatar;
+#[cfg(feature = "serde")]
+use crate::Parameters;
+use crate::RenderOptions;
+
+#[test]
+fn test_from_parameters() {
+    let options = RenderOptions::new(32);
+
+    let expected = avatar();
+    let avatar = Avatar::from_parameters(expected.parameters().clone()).unwrap();
+
+    assert_eq!(
+        expected.render(&options).unwrap(),
+        avatar.render(&options).unwrap()
+    );
+}
+
+#[test]
+fn test_from_parameters_invalid() {
+    let avatar = avatar();
+
+    let mut parameters = avatar.parameters().clone();
+    parameters.data.hair_lengths.pop();
+
+    assert!(Avatar::from_parameters(parameters).is_err());
+
+    let mut parameters = avatar.parameters().clone();
+    parameters.background.cloud_sizes.clear();
+
+    assert!(Avatar::from_parameters(parameters).is_err());
+
+    let mut parameters = avatar.parameters().clone();
+    parameters.focal_length = 0.0;
+
+    assert!(Avatar::from_parameters(parameters).is_err());
+
+    let mut parameters = avatar.parameters().clone();
+    parameters.background.horizon = 0.2;
+
+    assert!(Avatar::from_parameters(parameters).is_err());
+
+    let mut parameters = avatar.parameters().clone();
+    parameters.data.horn_hue = 360;
+
+    assert!(Avatar::from_parameters(parameters).is_err());
+
+    let mut parameters = avatar.parameters().clone();
+    parameters.background.rainbow_band_width = -0.01;
+
+    assert!(Avatar::from_parameters(parameters).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_round_trip() {
+    let options = RenderOptions::new(32);
+
+    let expected = avatar();
+
+    let json = expected.parameters().to_json().unwrap();
+    let parameters = Parameters::from_json(&json).unwrap();
+
+    assert_eq!(expected.data().hair_gammas, parameters.data.hair_gammas);
+    assert_eq!(expected.parameters().scale_factor, parameters.scale_factor);
+
+    let avatar = Avatar::from_parameters(parameters).unwrap();
+
+    assert_eq!(
+        expected.render(&options).unwrap(),
+        avatar.render(&options).unwrap()
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_edited() {
+    let avatar = avatar();
+
+    let mut json: serde_json::Value =
+        serde_json::from_str(&avatar.parameters().to_json().unwrap()).unwrap();
+
+    assert!(json["data"]["pose"]["kind"].is_string());
+
+    json["data"]["horn_length"] = serde_json::json!(120.0);
+    json["data"]["pose"] = serde_json::json!({ "kind": "Walk", "phase": 0.5 });
+
+    let parameters = Parameters::from_json(&json.to_string()).unwrap();
+
+    assert_eq!(120.0, parameters.data.horn_length);
+    assert!(Avatar::from_parameters(parameters).is_ok());
+
+    assert!(Parameters::from_json("{}").is_err());
+
+    json["data"]["hair_starts"] = serde_json::json!([]);
+
+    assert!(Parameters::from_json(&json.to_string()).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_low_horizon() {
+    let avatar = avatar();
+
+    let mut json: serde_json::Value =
+        serde_json::from_str(&avatar.parameters().to_json().unwrap()).unwrap();
+
+    json["background"]["horizon"] = serde_json::json!(0.2);
+
+    let e = Parameters::from_json(&json.to_string()).unwrap_err();
+    assert_eq!("Horizon must be 0.5 to 1, not 0.2", e.to_string());
+}
+
+#[test]
+fn test_to_json() {
+    let avatar = avatar();
+
+    let json = avatar.parameters().to_json().unwrap();
+
+    assert!(json.starts_with("{\n  \"data\": {\n    \"head_size\": "));
+    assert!(json.contains("\n    \"pose\": {\n      \"kind\": \"RotaryGallop\",\n"));
+    assert!(json.contains("\n    \"hair_count\": 52,\n"));
+    assert!(json.ends_with("\n  }\n}"));
+
+    let mut parameters = avatar.parameters().clone();
+    parameters.background.horizon = std::f64::NAN;
+
+    assert!(parameters.to_json().is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_to_json_matches_serde() {
+    let parameters = avatar().parameters().clone();
+
+    let serde = serde_json::to_string_pretty(&parameters).unwrap();
+    let json = parameters.to_json().unwrap();
+
+    let expected: serde_json::Value = serde_json::from_str(&serde).unwrap();
+    let actual: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(expected, actual);
+    assert_eq!(serde, json);
+}
+
+#[test]
+fn test_display() {
+    let avatar = avatar();
+    let parameters = avatar.parameters();
+
+    let text = parameters.to_string();
+
+    assert!(text.contains("  pose:               rotary gallop, phase 0.0888"));
+    assert!(text.contains("  hair count:         52\n"));
+    assert!(text.contains("  horn angle:         27\n"));
+    assert!(text.contains(&format!(
+        "  scale factor:       {}\n",
+        parameters.scale_factor
+    )));
+    assert_eq!(52, text.matches("tip lightness").count());
+}
@@ -0,0 +1,48 @@
+use crate::test_fixtures::avatar;
+use crate::RenderOptions;
+
+#[test]
+fn test_render_pov() {
+    let avatar = avatar();
+
+    let pov = avatar.render_pov(&RenderOptions::new(128)).unwrap();
+
+    assert!(pov.starts_with("// povray +W128 +H128 +UA\n#version 3.7;\n"));
+    assert!(pov.contains("background { srgb <"));
+    assert!(pov.contains("sphere_sweep { linear_spline 2, <"));
+    assert!(pov.contains("gradient x color_map"));
+    assert!(pov.ends_with("object { Unicorn }\n"));
+
+    assert_eq!(pov.matches('{').count(), pov.matches('}').count());
+}
+
+#[test]
+fn test_render_pov_window() {
+    let avatar = avatar();
+
+    let mut options = RenderOptions::new(128);
+    options.background = false;
+
+    let full = avatar.render_pov(&options).unwrap();
+
+    options.quadrant = Some(2);
+
+    let quadrant = avatar.render_pov(&options).unwrap();
+
+    assert!(quadrant.starts_with("// povray +W64 +H64 +UA\n"));
+    assert!(quadrant.contains("background { rgbt <0, 0, 0, 1> }"));
+
+    let camera = |pov: &str| {
+        let start = pov.find("camera").unwrap();
+        let end = pov[start..].find('}').unwrap();
+
+        pov[start..start + end].to_string()
+    };
+
+    assert_ne!(camera(&full), camera(&quadrant));
+
+    // the unicorn is the same, only the camera moves
+    let unicorn = |pov: &str| pov[pov.find("#declare").unwrap()..].to_string();
+
+    assert_eq!(unicorn(&full), unicorn(&quadrant));
+}
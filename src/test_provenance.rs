@@ -0,0 +1,174 @@
+use crate::test_fixtures::HASH;
+use crate::Avatar;
+use crate::OutputFormat;
+use crate::Provenance;
+use crate::Region;
+use crate::RenderOptions;
+use crate::RENDER_VERSION;
+
+use image::ImageFormat;
+use image::Rgba;
+use image::RgbaImage;
+
+fn png(options: &RenderOptions) -> Vec<u8> {
+    let avatar = Avatar::new(HASH.to_string(), options.zoom_out).unwrap();
+    let image = avatar.render(options).unwrap();
+
+    Provenance::new(HASH, options).encode_png(&image).unwrap()
+}
+
+// A small image that is not an avatar
+fn plain() -> RgbaImage {
+    RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255]))
+}
+
+#[test]
+fn test_entries() {
+    let mut options = RenderOptions::new(64);
+    options.region = Some(Region::new(0, 8, 32, 16));
+    options.zoom_out = true;
+
+    let entries = Provenance::new(HASH, &options).entries();
+
+    let expected = vec![
+        (
+            "Software",
+            format!("unicornify {}", env!("CARGO_PKG_VERSION")),
+        ),
+        ("Unicornify Hash", HASH.to_string()),
+        ("Unicornify Render Version", RENDER_VERSION.to_string()),
+        ("Unicornify Size", String::from("64")),
+        ("Unicornify Region", String::from("0,8,32,16")),
+        ("Unicornify Options", String::from("background zoom-out")),
+    ];
+
+    assert_eq!(expected, entries);
+}
+
+#[test]
+fn test_read() {
+    let mut options = RenderOptions::new(32);
+    options.quadrant = Some(3);
+    options.background = false;
+
+    let png = png(&options);
+
+    // still a valid PNG
+    let image = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+    assert_eq!(16, image.into_rgba8().width());
+
+    let provenance = Provenance::read(&png).unwrap();
+
+    assert_eq!(Provenance::new(HASH, &options), provenance);
+}
+
+#[test]
+fn test_read_international_text() {
+    let provenance = Provenance::new(HASH, &RenderOptions::new(16));
+
+    let plain = OutputFormat::Png.encode(&plain()).unwrap();
+    let embedded = provenance.embed(&plain).unwrap();
+
+    // rewrite the hash chunk as an uncompressed iTXt chunk with the same keyword
+    let keyword = b"Unicornify Hash\0";
+    let start = embedded
+        .windows(keyword.len())
+        .position(|w| w == keyword)
+        .unwrap()
+        - 8;
+
+    let mut data = keyword.to_vec();
+    data.extend_from_slice(&[0, 0, 0, 0]);
+    data.extend_from_slice(HASH.as_bytes());
+
+    let mut itxt = (data.len() as u32).to_be_bytes().to_vec();
+    itxt.extend_from_slice(b"iTXt");
+    itxt.extend_from_slice(&data);
+    // the CRC is not checked
+    itxt.extend_from_slice(&[0; 4]);
+
+    let end = start + 12 + keyword.len() + HASH.len();
+
+    let mut png = embedded[..start].to_vec();
+    png.extend_from_slice(&itxt);
+    png.extend_from_slice(&embedded[end..]);
+
+    assert_eq!(provenance, Provenance::read(&png).unwrap());
+}
+
+#[test]
+fn test_embed_international_text() {
+    let hash = "caf\u{e9} \u{1f984}";
+    let provenance = Provenance::new(hash, &RenderOptions::new(16));
+
+    let plain = OutputFormat::Png.encode(&plain()).unwrap();
+    let png = provenance.embed(&plain).unwrap();
+
+    // only the hash is not Latin-1
+    let itxt = b"iTXtUnicornify Hash\0\0\0\0\0";
+    let count = png.windows(4).filter(|w| w == b"iTXt").count();
+
+    assert_eq!(1, count);
+    assert!(png.windows(itxt.len()).any(|w| w == itxt));
+
+    // the decoder checks the CRCs
+    assert!(image::load_from_memory_with_format(&png, ImageFormat::Png).is_ok());
+
+    assert_eq!(provenance, Provenance::read(&png).unwrap());
+
+    // Latin-1 text stays in tEXt
+    let latin1 = Provenance::new("caf\u{e9}", &RenderOptions::new(16));
+    let png = latin1.embed(&plain).unwrap();
+
+    assert!(!png.windows(4).any(|w| w == b"iTXt"));
+    assert_eq!(latin1, Provenance::read(&png).unwrap());
+}
+
+#[test]
+fn test_read_missing() {
+    let png = OutputFormat::Png.encode(&plain()).unwrap();
+
+    let e = Provenance::read(&png).unwrap_err();
+    assert_eq!("The PNG has no unicornify provenance", e.to_string());
+
+    let e = Provenance::read(b"GIF89a").unwrap_err();
+    assert_eq!("Not a PNG image", e.to_string());
+}
+
+#[test]
+fn test_encode() {
+    let provenance = Provenance::new(HASH, &RenderOptions::new(16));
+    let image = plain();
+
+    let png = provenance.encode(OutputFormat::Png, &image).unwrap();
+    assert!(Provenance::read(&png).is_ok());
+
+    let bmp = provenance.encode(OutputFormat::Bmp, &image).unwrap();
+    assert_eq!(OutputFormat::Bmp.encode(&image).unwrap(), bmp);
+}
+
+#[test]
+fn test_verify() {
+    let options = RenderOptions::new(24);
+    let png = png(&options);
+
+    let provenance = Provenance::read(&png).unwrap();
+    assert_eq!(0, provenance.verify(&png, 2).unwrap());
+
+    // a different avatar with the same provenance
+    let other = Avatar::new("0".repeat(32), false).unwrap();
+    let image = other.render(&options).unwrap();
+    let forged = provenance.encode_png(&image).unwrap();
+
+    assert!(provenance.verify(&forged, 1).unwrap() > 0);
+}
+
+#[test]
+fn test_verify_render_version() {
+    let png = png(&RenderOptions::new(16));
+
+    let mut provenance = Provenance::read(&png).unwrap();
+    provenance.render_version = RENDER_VERSION + 1;
+
+    assert!(provenance.verify(&png, 1).is_err());
+}
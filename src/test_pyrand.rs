@@ -1,4 +1,5 @@
 use crate::pyrand::Random;
+use crate::pyrand::RandomState;
 
 // ten digits after the decimal point
 fn tendigits(value: f64) -> String {
@@ -59,6 +60,46 @@ fn test_rand_seed_hex_string() {
     );
 }
 
+fn gen_str(seed: &str, iterations: u32) -> String {
+    let mut r = Random::new();
+
+    r.seed_str(seed).unwrap();
+
+    for _ in 0..iterations {
+        r.rand();
+    }
+
+    tendigits(r.rand())
+}
+
+#[test]
+fn test_rand_seed_str() {
+    assert_eq!(gen_str("hello", 0), "0.3537754404");
+    assert_eq!(gen_str("hello", 1000), "0.8399094033");
+    assert_eq!(gen_str("", 0), "0.9602256525");
+    assert_eq!(gen_str("Ünïcörn", 0), "0.7177214891");
+    assert_eq!(gen_str("someone@example.com", 5000), "0.9219995155");
+}
+
+fn gen_byte_string(seed: &[u8], iterations: u32) -> String {
+    let mut r = Random::new();
+
+    r.seed_byte_string(seed).unwrap();
+
+    for _ in 0..iterations {
+        r.rand();
+    }
+
+    tendigits(r.rand())
+}
+
+#[test]
+fn test_rand_seed_byte_string() {
+    assert_eq!(gen_byte_string(b"\x00\x00\xffabc", 0), "0.9362968901");
+    assert_eq!(gen_byte_string(b"\x00\x00\xffabc", 777), "0.0927448007");
+    assert_eq!(gen_byte_string(b"", 0), "0.9602256525");
+}
+
 fn gen_u64(seed: u64, iterations: u32) -> String {
     let mut r = Random::new();
 
@@ -162,3 +203,240 @@ fn gen_i32_seed_u64(seed: u64, iterations: u32, a: i32, b: i32) -> i32 {
 fn test_rand_i32_seed_u64() {
     assert_eq!(gen_i32_seed_u64(432153415134, 986, -12307, -803), -4223);
 }
+
+fn seeded(seed: u32) -> Random {
+    let mut r = Random::new();
+
+    r.seed_u32(seed);
+
+    r
+}
+
+#[test]
+fn test_uniform() {
+    let mut r = seeded(1234);
+
+    assert_eq!(tendigits(r.uniform(-5.0, 10.0)), "9.4968030353");
+    assert_eq!(tendigits(r.uniform(-5.0, 10.0)), "1.6109889876");
+    assert_eq!(tendigits(r.uniform(-5.0, 10.0)), "-4.887627949");
+}
+
+#[test]
+fn test_gauss() {
+    let mut r = seeded(1234);
+
+    assert_eq!(tendigits(r.gauss(10.0, 2.0)), "12.108439283");
+    assert_eq!(tendigits(r.gauss(10.0, 2.0)), "9.5488854885");
+    assert_eq!(tendigits(r.gauss(10.0, 2.0)), "14.394081096");
+    assert_eq!(tendigits(r.gauss(10.0, 2.0)), "10.206983579");
+    assert_eq!(tendigits(r.gauss(10.0, 2.0)), "12.452395059");
+
+    r.seed_u32(1234);
+    r.gauss(0.0, 1.0);
+    r.seed_u32(1234);
+
+    assert_eq!(tendigits(r.gauss(10.0, 2.0)), "12.108439283");
+}
+
+#[test]
+fn test_normal_variate() {
+    let mut r = seeded(1234);
+
+    assert_eq!(tendigits(r.normal_variate(10.0, 2.0)), "12.861650769");
+    assert_eq!(tendigits(r.normal_variate(10.0, 2.0)), "13.607601243");
+    assert_eq!(tendigits(r.normal_variate(10.0, 2.0)), "10.642580938");
+}
+
+#[test]
+fn test_shuffle() {
+    let mut r = seeded(1234);
+    let mut items: Vec<u32> = (0..10).collect();
+
+    r.shuffle(&mut items);
+
+    assert_eq!(items, vec![2, 8, 3, 5, 6, 4, 9, 0, 1, 7]);
+}
+
+#[test]
+fn test_sample() {
+    let mut r = seeded(1234);
+
+    let small: Vec<u32> = (0..10).collect();
+    let medium: Vec<u32> = (0..100).collect();
+    let large: Vec<u32> = (0..1000).collect();
+
+    assert_eq!(r.sample(&small, 4).unwrap(), vec![7, 1, 0, 9]);
+    assert_eq!(
+        r.sample(&medium, 10).unwrap(),
+        vec![74, 4, 85, 88, 10, 12, 98, 45, 30, 2]
+    );
+    assert_eq!(r.sample(&large, 3).unwrap(), vec![31, 807, 16]);
+}
+
+#[test]
+fn test_sample_too_large() {
+    let mut r = seeded(1234);
+
+    let e = r.sample(&[1, 2, 3], 4).unwrap_err();
+    assert_eq!(
+        "Sample of 4 is larger than the population of 3",
+        e.to_string()
+    );
+
+    assert!(r.sample::<u32>(&[], 0).unwrap().is_empty());
+}
+
+#[test]
+fn test_choices() {
+    let mut r = seeded(1234);
+
+    assert_eq!(
+        r.choices(&['a', 'b', 'c', 'd', 'e'], None, 5).unwrap(),
+        vec!['e', 'c', 'a', 'e', 'e']
+    );
+    assert_eq!(
+        r.choices(&['a', 'b', 'c'], Some(&[1.0, 0.0, 3.0]), 6)
+            .unwrap(),
+        vec!['c', 'c', 'a', 'c', 'a', 'a']
+    );
+}
+
+#[test]
+fn test_choices_invalid() {
+    let mut r = seeded(1234);
+    let population = ['a', 'b', 'c'];
+
+    let e = r.choices::<char>(&[], None, 1).unwrap_err();
+    assert_eq!("Cannot choose from an empty population", e.to_string());
+
+    assert!(r.choices::<char>(&[], None, 0).unwrap().is_empty());
+
+    let e = r.choices(&population, Some(&[1.0, 2.0]), 1).unwrap_err();
+    assert_eq!("2 weights do not match the population of 3", e.to_string());
+
+    let e = r
+        .choices(&population, Some(&[1.0, -1.0, 3.0]), 1)
+        .unwrap_err();
+    assert_eq!("Weights must not be negative, not -1", e.to_string());
+
+    let e = r.choices(&population, Some(&[0.0; 3]), 1).unwrap_err();
+    assert_eq!(
+        "Total of weights must be greater than zero and finite, not 0",
+        e.to_string()
+    );
+
+    let e = r.choices::<char>(&[], Some(&[]), 1).unwrap_err();
+    assert_eq!(
+        "Total of weights must be greater than zero and finite, not 0",
+        e.to_string()
+    );
+
+    assert!(r
+        .choices(&population, Some(&[1.0, std::f64::NAN, 1.0]), 1)
+        .is_err());
+}
+
+#[test]
+fn test_triangular() {
+    let mut r = seeded(1234);
+
+    assert_eq!(tendigits(r.triangular(0.0, 10.0, None)), "8.7048848640");
+    assert_eq!(tendigits(r.triangular(0.0, 10.0, None)), "4.6943189025");
+    assert_eq!(tendigits(r.triangular(0.0, 10.0, None)), "0.6120241032");
+    assert_eq!(
+        tendigits(r.triangular(0.0, 10.0, Some(8.0))),
+        "8.6656534366"
+    );
+    assert_eq!(
+        tendigits(r.triangular(0.0, 10.0, Some(8.0))),
+        "8.8979019768"
+    );
+    assert_eq!(
+        tendigits(r.triangular(0.0, 10.0, Some(8.0))),
+        "6.8248227702"
+    );
+    assert_eq!(r.triangular(3.0, 3.0, Some(3.0)), 3.0);
+}
+
+#[test]
+fn test_expo_variate() {
+    let mut r = seeded(1234);
+
+    assert_eq!(tendigits(r.expo_variate(1.5)), "2.2632158712");
+    assert_eq!(tendigits(r.expo_variate(1.5)), "0.3874183763");
+    assert_eq!(tendigits(r.expo_variate(1.5)), "0.0050131147");
+}
+
+#[test]
+fn test_get_state() {
+    let mut r = seeded(1234);
+
+    let state = r.get_state().to_string();
+
+    assert!(state.starts_with("(3, (2147483648, 681931688, 3687344416, "));
+    assert!(state.ends_with(", 343805460, 624), None)"));
+
+    for _ in 0..3 {
+        r.rand();
+    }
+    r.gauss(0.0, 1.0);
+
+    let state = r.get_state().to_string();
+
+    assert!(state.starts_with("(3, (1181343704, 676652175, 2664188706, 4266926488, "));
+    assert!(state.ends_with(", 845107718, 2735096863, 10), -1.2560132373699584)"));
+}
+
+#[test]
+fn test_set_state() {
+    let mut r = seeded(1234);
+
+    for _ in 0..3 {
+        r.rand();
+    }
+    r.gauss(0.0, 1.0);
+
+    let state: RandomState = r.get_state().to_string().parse().unwrap();
+
+    let mut restored = Random::new();
+    restored.set_state(&state).unwrap();
+
+    assert_eq!(restored.rand(), 0.5822275730589491);
+    assert_eq!(restored.gauss(0.0, 1.0), -1.2560132373699584);
+
+    let mut invalid = state.clone();
+    invalid.index = 625;
+
+    assert!(restored.set_state(&invalid).is_err());
+}
+
+#[test]
+fn test_random_state_gauss_next() {
+    let mut state = seeded(0).get_state();
+
+    let cases = [
+        (1e-05, "1e-05"),
+        (0.0001, "0.0001"),
+        (1e16, "1e+16"),
+        (1234567890123456.0, "1234567890123456.0"),
+        (-2.5e-7, "-2.5e-07"),
+        (-0.0, "-0.0"),
+        (12.0, "12.0"),
+    ];
+
+    for (value, repr) in cases.iter() {
+        state.gauss_next = Some(*value);
+
+        let formatted = state.to_string();
+
+        assert!(formatted.ends_with(&format!("), {})", repr)), "{}", repr);
+        assert_eq!(state, formatted.parse().unwrap());
+    }
+}
+
+#[test]
+fn test_random_state_parse_invalid() {
+    assert!("(2, (1, 2), None)".parse::<RandomState>().is_err());
+    assert!("(3, (1, 2, 3), None)".parse::<RandomState>().is_err());
+    assert!("(3, (1, 2, 3))".parse::<RandomState>().is_err());
+}
@@ -0,0 +1,61 @@
+use crate::test_fixtures::avatar;
+use crate::Region;
+use crate::RenderOptions;
+
+use image::imageops;
+
+#[test]
+fn test_region_parse() {
+    let region: Region = "1, 2,30,40".parse().unwrap();
+
+    assert_eq!(Region::new(1, 2, 30, 40), region);
+    assert_eq!("1,2,30,40", region.to_string());
+
+    assert!("1,2,3".parse::<Region>().is_err());
+    assert!("1,2,3,4,5".parse::<Region>().is_err());
+    assert!("1,2,3,-4".parse::<Region>().is_err());
+}
+
+#[test]
+fn test_validate_region() {
+    let mut options = RenderOptions::new(32);
+
+    options.region = Some(Region::new(16, 16, 16, 16));
+    assert!(options.validate().is_ok());
+
+    options.region = Some(Region::new(16, 16, 17, 16));
+    assert!(options.validate().is_err());
+
+    options.region = Some(Region::new(0, 0, 0, 16));
+    assert!(options.validate().is_err());
+
+    options.region = Some(Region::new(0, 0, 16, 16));
+    options.quadrant = Some(1);
+    assert!(options.validate().is_err());
+}
+
+#[test]
+fn test_render_region() {
+    let avatar = avatar();
+    let mut options = RenderOptions::new(32);
+
+    let full = avatar.render(&options).unwrap();
+
+    options.region = Some(Region::new(4, 8, 20, 12));
+    let region = avatar.render(&options).unwrap();
+
+    let expected = imageops::crop_imm(&full, 4, 8, 20, 12).to_image();
+
+    assert_eq!(expected, region);
+}
+
+#[test]
+fn test_render_threaded() {
+    let avatar = avatar();
+    let options = RenderOptions::new(48);
+
+    let single = avatar.render(&options).unwrap();
+
+    assert_eq!(single, avatar.render_threaded(&options, 3).unwrap());
+    assert!(avatar.render_threaded(&options, 0).is_err());
+}
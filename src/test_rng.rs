@@ -0,0 +1,61 @@
+use crate::test_fixtures::avatar;
+use crate::test_fixtures::HASH;
+use crate::Avatar;
+use crate::Random;
+use crate::RenderOptions;
+use crate::Rng;
+
+// Forwards to Random and counts the numbers drawn, standing in for any other generator
+struct Counting {
+    random: Random,
+    count: usize,
+}
+
+impl Rng for Counting {
+    fn rand(&mut self) -> f64 {
+        self.count += 1;
+        self.random.rand()
+    }
+
+    fn rand_bits(&mut self, k: u32) -> Vec<u32> {
+        self.count += 1;
+        self.random.rand_bits(k)
+    }
+}
+
+#[test]
+fn test_default_methods_match_random() {
+    let mut random = Random::new();
+    random.seed_u32(519876);
+
+    let mut counting = Counting {
+        random: Random::new(),
+        count: 0,
+    };
+    counting.random.seed_u32(519876);
+
+    for _ in 0..100 {
+        assert_eq!(random.rand_i32(-12307, 803), counting.rand_i32(-12307, 803));
+        assert_eq!(random.choice(7), counting.choice(7));
+    }
+
+    assert_eq!(200, counting.count);
+}
+
+#[test]
+fn test_avatar_from_rng() {
+    let mut random = Random::new();
+    random.seed_hex_string(String::from(HASH)).unwrap();
+
+    let mut counting = Counting { random, count: 0 };
+
+    let options = RenderOptions::new(32);
+
+    let expected = avatar().render(&options).unwrap();
+    let image = Avatar::from_rng(&mut counting, false)
+        .render(&options)
+        .unwrap();
+
+    assert!(counting.count > 0);
+    assert_eq!(expected, image);
+}
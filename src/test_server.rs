@@ -0,0 +1,142 @@
+use crate::test_fixtures::HASH;
+use crate::AvatarCache;
+use crate::Server;
+
+use std::io::Read;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+fn start() -> SocketAddr {
+    start_with_workers(4)
+}
+
+fn start_with_workers(workers: usize) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = Server::new(AvatarCache::new(4), 32).with_workers(workers);
+
+    thread::spawn(move || server.serve(listener).unwrap());
+
+    address
+}
+
+// Returns the response head and body
+fn request(address: SocketAddr, request: &str) -> (String, Vec<u8>) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+
+    // a rejected request may be reset after the response arrived
+    let mut response = Vec::new();
+    let _ = stream.read_to_end(&mut response);
+
+    let end = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+    let head = String::from_utf8(response[..end].to_vec()).unwrap();
+
+    (head, response[end + 4..].to_vec())
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines()
+        .filter_map(|l| l.split_once(": "))
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v)
+}
+
+#[test]
+fn test_avatar() {
+    let address = start();
+
+    let get = format!("GET /avatar/{}?s=16 HTTP/1.1\r\nHost: test\r\n\r\n", HASH);
+    let (head, body) = request(address, &get);
+
+    assert!(head.starts_with("HTTP/1.1 200 OK"), "{}", head);
+    assert_eq!(Some("image/png"), header(&head, "content-type"));
+    assert_eq!(&body[1..4], b"PNG");
+
+    let image = image::load_from_memory(&body).unwrap().to_rgba8();
+    assert_eq!(16, image.width());
+
+    let etag = header(&head, "etag").unwrap();
+    assert!(etag.contains(env!("CARGO_PKG_VERSION")), "{}", etag);
+
+    let cached = format!(
+        "GET /avatar/{}?s=16 HTTP/1.1\r\nIf-None-Match: {}\r\n\r\n",
+        HASH, etag
+    );
+    let (head, body) = request(address, &cached);
+
+    assert!(head.starts_with("HTTP/1.1 304 Not Modified"), "{}", head);
+    assert!(body.is_empty());
+
+    let zoomed = format!("GET /avatar/{}?s=16&zoomout=1 HTTP/1.1\r\n\r\n", HASH);
+    let (head, _) = request(address, &zoomed);
+
+    assert_ne!(Some(etag), header(&head, "etag"));
+}
+
+#[test]
+fn test_avatar_size_clamped() {
+    let address = start();
+
+    let get = format!("GET /avatar/{}?s=4096&background=0 HTTP/1.1\r\n\r\n", HASH);
+    let (head, body) = request(address, &get);
+
+    assert!(head.starts_with("HTTP/1.1 200 OK"), "{}", head);
+
+    let image = image::load_from_memory(&body).unwrap().to_rgba8();
+    assert_eq!(32, image.width());
+    assert_eq!(0, image.get_pixel(0, 0)[3]);
+}
+
+#[test]
+fn test_errors() {
+    let address = start();
+
+    let (head, _) = request(address, "GET /avatar/xyz HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 400"), "{}", head);
+
+    let get = format!("GET /avatar/{}?s=0 HTTP/1.1\r\n\r\n", HASH);
+    let (head, _) = request(address, &get);
+    assert!(head.starts_with("HTTP/1.1 400"), "{}", head);
+
+    let (head, _) = request(address, "GET /other HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 404"), "{}", head);
+
+    let post = format!("POST /avatar/{} HTTP/1.1\r\n\r\n", HASH);
+    let (head, _) = request(address, &post);
+    assert!(head.starts_with("HTTP/1.1 405"), "{}", head);
+}
+
+#[test]
+fn test_busy() {
+    let address = start_with_workers(1);
+
+    // a client that sends nothing holds the only worker
+    let slow = TcpStream::connect(address).unwrap();
+
+    let get = format!("GET /avatar/{}?s=16 HTTP/1.1\r\n\r\n", HASH);
+    let (head, _) = request(address, &get);
+
+    assert!(head.starts_with("HTTP/1.1 503"), "{}", head);
+    assert_eq!(Some("1"), header(&head, "retry-after"));
+
+    drop(slow);
+
+    // the worker is free once the slow connection is closed
+    for _ in 0..50 {
+        let (head, _) = request(address, &get);
+
+        if head.starts_with("HTTP/1.1 200") {
+            return;
+        }
+
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    panic!("the worker was not freed");
+}
@@ -0,0 +1,44 @@
+use crate::test_fixtures::avatar;
+use crate::Region;
+use crate::RenderOptions;
+
+#[test]
+fn test_render_svg() {
+    let avatar = avatar();
+
+    let svg = avatar.render_svg(&RenderOptions::new(128)).unwrap();
+
+    assert!(svg.starts_with(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"128\" height=\"128\" viewBox=\"0 0 128 128\">"
+    ));
+    assert!(svg.ends_with("</svg>\n"));
+
+    assert!(svg.contains("fill=\"url(#sky)\""));
+    assert_eq!(7, svg.matches("stroke-width").count());
+    assert!(svg.matches("<path d=\"M").count() > 20);
+}
+
+#[test]
+fn test_render_svg_view() {
+    let avatar = avatar();
+
+    let mut options = RenderOptions::new(128);
+    options.background = false;
+    options.quadrant = Some(4);
+
+    let svg = avatar.render_svg(&options).unwrap();
+
+    assert!(svg.contains("width=\"64\" height=\"64\" viewBox=\"64 64 64 64\""));
+    assert!(!svg.contains("#sky"));
+
+    options.quadrant = None;
+    options.region = Some(Region::new(10, 20, 30, 40));
+
+    let svg = avatar.render_svg(&options).unwrap();
+
+    assert!(svg.contains("width=\"30\" height=\"40\" viewBox=\"10 20 30 40\""));
+
+    options.shading = true;
+
+    assert!(avatar.render_svg(&options).is_err());
+}
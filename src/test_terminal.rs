@@ -0,0 +1,54 @@
+use crate::terminal::half_blocks;
+use crate::terminal::sixel;
+
+use image::Rgba;
+use image::RgbaImage;
+
+#[test]
+fn test_half_blocks() {
+    let mut image = RgbaImage::new(3, 3);
+
+    let red = Rgba([255, 0, 0, 255]);
+    let blue = Rgba([0, 0, 255, 255]);
+
+    image.put_pixel(0, 0, red);
+    image.put_pixel(0, 1, blue);
+    image.put_pixel(1, 0, red);
+    image.put_pixel(2, 1, blue);
+    image.put_pixel(2, 2, red);
+
+    let expected = concat!(
+        "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀\x1b[49m▀\x1b[38;2;0;0;255m▄\x1b[0m\n",
+        "  \x1b[38;2;255;0;0m▀\x1b[0m\n",
+    );
+
+    assert_eq!(expected, half_blocks(&image));
+}
+
+#[test]
+fn test_sixel() {
+    let mut image = RgbaImage::new(8, 7);
+
+    for x in 0..8 {
+        image.put_pixel(x, 0, Rgba([255, 255, 255, 255]));
+    }
+
+    image.put_pixel(0, 6, Rgba([255, 255, 255, 255]));
+
+    let sixel = sixel(&image);
+
+    assert!(sixel.starts_with("\x1bP0;1;0q\"1;1;8;7#"));
+    assert!(sixel.contains(";2;100;100;100"));
+    assert!(sixel.ends_with("\x1b\\"));
+
+    let row = sixel.find("!8@$-").unwrap();
+    let color = &sixel[sixel[..row].rfind('#').unwrap()..row];
+
+    // a full top row, then the bottom left pixel in the next band
+    assert!(sixel.ends_with(&format!("{}!8@$-{}@$-\x1b\\", color, color)));
+
+    assert_eq!(
+        "\x1bP0;1;0q\"1;1;2;2\x1b\\",
+        crate::terminal::sixel(&RgbaImage::new(2, 2))
+    );
+}
@@ -0,0 +1,58 @@
+use crate::animation::FRAME_DELAY;
+use crate::Animation;
+use crate::Y4mWriter;
+
+use image::Rgba;
+use image::RgbaImage;
+
+#[test]
+fn test_write_frame() {
+    let mut frame = RgbaImage::from_pixel(3, 2, Rgba([255, 255, 255, 255]));
+    frame.put_pixel(2, 0, Rgba([255, 0, 0, 255]));
+    frame.put_pixel(2, 1, Rgba([255, 0, 0, 255]));
+
+    let mut y4m = Y4mWriter::new(Vec::new(), 3, 2, 30000, 1001).unwrap();
+    y4m.write_frame(&frame).unwrap();
+    let video = y4m.finish().unwrap();
+
+    let header = b"YUV4MPEG2 W3 H2 F30000:1001 Ip A1:1 C420jpeg\nFRAME\n";
+
+    assert_eq!(header, &video[..header.len()]);
+
+    // 6 luma samples then one 2x2 and one 1x2 chroma block for U and V
+    let planes = &video[header.len()..];
+
+    assert_eq!([235, 235, 81, 235, 235, 81, 128, 90, 128, 240], planes[..]);
+}
+
+#[test]
+fn test_write_frame_transparent() {
+    let frame = RgbaImage::from_pixel(2, 2, Rgba([255, 255, 255, 0]));
+
+    let mut y4m = Y4mWriter::new(Vec::new(), 2, 2, 25, 1).unwrap();
+    y4m.write_frame(&frame).unwrap();
+    let video = y4m.finish().unwrap();
+
+    assert_eq!([16, 16, 16, 16, 128, 128], video[video.len() - 6..]);
+}
+
+#[test]
+fn test_write_frame_size_mismatch() {
+    let mut y4m = Y4mWriter::new(Vec::new(), 4, 4, 25, 1).unwrap();
+
+    assert!(y4m.write_frame(&RgbaImage::new(4, 2)).is_err());
+    assert!(Y4mWriter::new(Vec::new(), 4, 4, 0, 1).is_err());
+}
+
+#[test]
+fn test_animation_write_y4m() {
+    let animation = Animation::new(vec![RgbaImage::new(2, 2); 3], FRAME_DELAY);
+
+    let mut video = Vec::new();
+    animation.write_y4m(&mut video).unwrap();
+
+    let video = String::from_utf8_lossy(&video);
+
+    assert!(video.starts_with("YUV4MPEG2 W2 H2 F25:1 "));
+    assert_eq!(3, video.matches("FRAME\n").count());
+}
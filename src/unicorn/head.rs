@@ -3,6 +3,7 @@ use crate::geometry::Ball;
 use crate::geometry::Bone;
 use crate::geometry::Vector;
 use crate::render::GroupTracer;
+use crate::render::TracerArena;
 use crate::render::WorldView;
 
 #[derive(Clone, Debug)]
@@ -46,17 +47,28 @@ impl Head {
         }
     }
 
-    pub fn add_traceable(&self, mut tracer: &mut GroupTracer, world_view: &WorldView) {
-        self.face.add_traceable(&mut tracer, world_view);
-        self.horn.add_traceable(&mut tracer, world_view);
-        self.eye_left.add_traceable(&mut tracer, world_view);
-        self.eye_right.add_traceable(&mut tracer, world_view);
-        self.pupil_left.add_traceable(&mut tracer, world_view);
-        self.pupil_right.add_traceable(&mut tracer, world_view);
-        self.brow_left_i.add_traceable(&mut tracer, world_view);
-        self.brow_left_o.add_traceable(&mut tracer, world_view);
-        self.brow_right_i.add_traceable(&mut tracer, world_view);
-        self.brow_right_o.add_traceable(&mut tracer, world_view);
+    pub fn add_traceable(
+        &self,
+        arena: &mut TracerArena,
+        mut tracer: &mut GroupTracer,
+        world_view: &WorldView,
+    ) {
+        self.face.add_traceable(arena, &mut tracer, world_view);
+        self.horn.add_traceable(arena, &mut tracer, world_view);
+        self.eye_left.add_traceable(arena, &mut tracer, world_view);
+        self.eye_right.add_traceable(arena, &mut tracer, world_view);
+        self.pupil_left
+            .add_traceable(arena, &mut tracer, world_view);
+        self.pupil_right
+            .add_traceable(arena, &mut tracer, world_view);
+        self.brow_left_i
+            .add_traceable(arena, &mut tracer, world_view);
+        self.brow_left_o
+            .add_traceable(arena, &mut tracer, world_view);
+        self.brow_right_i
+            .add_traceable(arena, &mut tracer, world_view);
+        self.brow_right_o
+            .add_traceable(arena, &mut tracer, world_view);
     }
 
     pub fn attachment(&self) -> Ball {
@@ -64,7 +76,7 @@ impl Head {
     }
 
     pub fn center(&self) -> Vector {
-        self.face.b2.center.borrow().clone()
+        self.face.b2.center.read().unwrap().clone()
     }
 
     pub fn rotate_around(&self, other: &Vector, angle: f64, axis: Axis) {
@@ -3,6 +3,7 @@ use crate::geometry::Ball;
 use crate::geometry::Bone;
 use crate::geometry::Vector;
 use crate::render::GroupTracer;
+use crate::render::TracerArena;
 use crate::render::WorldView;
 
 #[derive(Clone, Debug)]
@@ -28,9 +29,14 @@ impl Leg {
         }
     }
 
-    pub fn add_traceable(&self, mut tracer: &mut GroupTracer, world_view: &WorldView) {
-        self.calf.add_traceable(&mut tracer, world_view);
-        self.shin.add_traceable(&mut tracer, world_view);
+    pub fn add_traceable(
+        &self,
+        arena: &mut TracerArena,
+        mut tracer: &mut GroupTracer,
+        world_view: &WorldView,
+    ) {
+        self.calf.add_traceable(arena, &mut tracer, world_view);
+        self.shin.add_traceable(arena, &mut tracer, world_view);
     }
 
     pub fn rotate_around(&self, other: &Vector, angle: f64, axis: Axis) {
@@ -2,6 +2,7 @@ use crate::geometry::Axis;
 use crate::geometry::Ball;
 use crate::geometry::Vector;
 use crate::render::GroupTracer;
+use crate::render::TracerArena;
 use crate::render::WorldView;
 use crate::unicorn::Leg;
 use crate::Color;
@@ -135,11 +136,16 @@ impl Legs {
         legs
     }
 
-    pub fn add_traceable(&self, mut tracer: &mut GroupTracer, world_view: &WorldView) {
-        self.fr.add_traceable(&mut tracer, world_view);
-        self.fl.add_traceable(&mut tracer, world_view);
-        self.br.add_traceable(&mut tracer, world_view);
-        self.bl.add_traceable(&mut tracer, world_view);
+    pub fn add_traceable(
+        &self,
+        arena: &mut TracerArena,
+        mut tracer: &mut GroupTracer,
+        world_view: &WorldView,
+    ) {
+        self.fr.add_traceable(arena, &mut tracer, world_view);
+        self.fl.add_traceable(arena, &mut tracer, world_view);
+        self.br.add_traceable(arena, &mut tracer, world_view);
+        self.bl.add_traceable(arena, &mut tracer, world_view);
     }
 
     pub fn rotate_around(&self, other: &Vector, angle: f64, axis: Axis) {
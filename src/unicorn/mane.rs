@@ -4,6 +4,7 @@ use crate::geometry::Bone;
 use crate::geometry::Gamma;
 use crate::geometry::Vector;
 use crate::render::GroupTracer;
+use crate::render::TracerArena;
 use crate::render::WorldView;
 use crate::Color;
 use crate::Data;
@@ -47,7 +48,11 @@ impl Mane {
             );
             let end_color = Color::hsl(data.hair_hue, data.hair_sat, data.hair_tip_lightnesses[i]);
             let hair_end = Ball::new_v(format!("hair {} end", i), end, 2.0, end_color);
-            hair_end.rotate_around(&hair_start.center.borrow(), -data.hair_angles[i], Axis::Z);
+            hair_end.rotate_around(
+                &hair_start.center.read().unwrap(),
+                -data.hair_angles[i],
+                Axis::Z,
+            );
 
             let hair = Bone::non_linear(
                 hair_start,
@@ -62,9 +67,14 @@ impl Mane {
         Mane { mane }
     }
 
-    pub fn add_traceable(&self, mut tracer: &mut GroupTracer, world_view: &WorldView) {
+    pub fn add_traceable(
+        &self,
+        arena: &mut TracerArena,
+        mut tracer: &mut GroupTracer,
+        world_view: &WorldView,
+    ) {
         for hair in self.mane.iter() {
-            hair.add_traceable(&mut tracer, world_view);
+            hair.add_traceable(arena, &mut tracer, world_view);
         }
     }
 
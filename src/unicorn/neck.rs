@@ -2,6 +2,7 @@ use crate::geometry::Axis;
 use crate::geometry::Bone;
 use crate::geometry::Vector;
 use crate::render::GroupTracer;
+use crate::render::TracerArena;
 use crate::render::WorldView;
 use crate::unicorn::Head;
 use crate::unicorn::Mane;
@@ -18,10 +19,15 @@ impl Neck {
         Neck { head, neck, mane }
     }
 
-    pub fn add_traceable(&self, mut tracer: &mut GroupTracer, world_view: &WorldView) {
-        self.head.add_traceable(&mut tracer, world_view);
-        self.neck.add_traceable(&mut tracer, world_view);
-        self.mane.add_traceable(&mut tracer, world_view);
+    pub fn add_traceable(
+        &self,
+        arena: &mut TracerArena,
+        mut tracer: &mut GroupTracer,
+        world_view: &WorldView,
+    ) {
+        self.head.add_traceable(arena, &mut tracer, world_view);
+        self.neck.add_traceable(arena, &mut tracer, world_view);
+        self.mane.add_traceable(arena, &mut tracer, world_view);
     }
 
     /// Rotates the neck (head, mane, and neck) around +other+, taking care not to rotate any
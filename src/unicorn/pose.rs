@@ -1,19 +1,23 @@
+use crate::generation_profile::choose;
 use crate::geometry::Axis;
 use crate::geometry::DEGREE;
-use crate::pyrand::Random;
 use crate::unicorn::Legs;
+use crate::GenerationProfile;
+use crate::Rng;
 use crate::Sorter;
 use crate::TV;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind"))]
 pub enum Pose {
     RotaryGallop { phase: f64 },
     Walk { phase: f64 },
 }
 
 impl Pose {
-    pub fn new(rand: &mut Random) -> Pose {
-        let kind = rand.choice(2);
+    pub fn new<R: Rng>(rand: &mut R, profile: &GenerationProfile) -> Pose {
+        let kind = choose(rand, &profile.pose);
         let phase = rand.rand();
 
         match kind {
@@ -23,6 +27,24 @@ impl Pose {
         }
     }
 
+    /// Where in its cycle the gait is, from 0 up to 1
+    pub fn phase(&self) -> f64 {
+        match self {
+            Pose::RotaryGallop { phase } => *phase,
+            Pose::Walk { phase } => *phase,
+        }
+    }
+
+    /// The same gait at +phase+, which wraps around at 1
+    pub fn with_phase(&self, phase: f64) -> Pose {
+        let phase = phase.rem_euclid(1.0);
+
+        match self {
+            Pose::RotaryGallop { .. } => Pose::RotaryGallop { phase },
+            Pose::Walk { .. } => Pose::Walk { phase },
+        }
+    }
+
     pub fn pose(&self, legs: &mut Legs) {
         match self {
             Pose::RotaryGallop { phase } => rotary_gallop(legs, *phase),
@@ -57,18 +79,18 @@ fn rotary_gallop(legs: &mut Legs, phase: f64) {
         TV::new(8.0 / 12.0, 50.0),
     ]);
 
-    let hip_center = &fr.hip.center.borrow();
+    let hip_center = &fr.hip.center.read().unwrap();
     fr.knee
         .rotate_around(hip_center, front_top.interpolate(phase) * DEGREE, Axis::Z);
     fr.hoof
         .rotate_around(hip_center, front_top.interpolate(phase) * DEGREE, Axis::Z);
     fr.hoof.rotate_around(
-        &fr.knee.center.borrow(),
+        &fr.knee.center.read().unwrap(),
         front_bottom.interpolate(phase) * DEGREE,
         Axis::Z,
     );
 
-    let hip_center = &fl.hip.center.borrow();
+    let hip_center = &fl.hip.center.read().unwrap();
     fl.knee.rotate_around(
         hip_center,
         front_top.interpolate(phase - 0.25) * DEGREE,
@@ -80,23 +102,23 @@ fn rotary_gallop(legs: &mut Legs, phase: f64) {
         Axis::Z,
     );
     fl.hoof.rotate_around(
-        &fl.knee.center.borrow(),
+        &fl.knee.center.read().unwrap(),
         front_bottom.interpolate(phase - 0.25) * DEGREE,
         Axis::Z,
     );
 
-    let hip_center = &br.hip.center.borrow();
+    let hip_center = &br.hip.center.read().unwrap();
     br.knee
         .rotate_around(hip_center, back_top.interpolate(phase) * DEGREE, Axis::Z);
     br.hoof
         .rotate_around(hip_center, back_top.interpolate(phase) * DEGREE, Axis::Z);
     br.hoof.rotate_around(
-        &br.knee.center.borrow(),
+        &br.knee.center.read().unwrap(),
         back_bottom.interpolate(phase) * DEGREE,
         Axis::Z,
     );
 
-    let hip_center = &bl.hip.center.borrow();
+    let hip_center = &bl.hip.center.read().unwrap();
     bl.knee.rotate_around(
         hip_center,
         back_top.interpolate(phase - 0.167) * DEGREE,
@@ -108,7 +130,7 @@ fn rotary_gallop(legs: &mut Legs, phase: f64) {
         Axis::Z,
     );
     bl.hoof.rotate_around(
-        &bl.knee.center.borrow(),
+        &bl.knee.center.read().unwrap(),
         back_bottom.interpolate(phase - 0.167) * DEGREE,
         Axis::Z,
     );
@@ -134,18 +156,18 @@ fn walk(legs: &mut Legs, phase: f64) {
     ]);
     let back_bottom = Sorter::new(vec![TV::new(5.0 / 9.0, 40.0), TV::new(9.0 / 9.0, 10.0)]);
 
-    let hip_center = &fr.hip.center.borrow();
+    let hip_center = &fr.hip.center.read().unwrap();
     fr.knee
         .rotate_around(hip_center, front_top.interpolate(phase) * DEGREE, Axis::Z);
     fr.hoof
         .rotate_around(hip_center, front_top.interpolate(phase) * DEGREE, Axis::Z);
     fr.hoof.rotate_around(
-        &fr.knee.center.borrow(),
+        &fr.knee.center.read().unwrap(),
         front_bottom.interpolate(phase) * DEGREE,
         Axis::Z,
     );
 
-    let hip_center = &fl.hip.center.borrow();
+    let hip_center = &fl.hip.center.read().unwrap();
     fl.knee.rotate_around(
         hip_center,
         front_top.interpolate(phase - 0.56) * DEGREE,
@@ -157,23 +179,23 @@ fn walk(legs: &mut Legs, phase: f64) {
         Axis::Z,
     );
     fl.hoof.rotate_around(
-        &fl.knee.center.borrow(),
+        &fl.knee.center.read().unwrap(),
         front_bottom.interpolate(phase - 0.56) * DEGREE,
         Axis::Z,
     );
 
-    let hip_center = &br.hip.center.borrow();
+    let hip_center = &br.hip.center.read().unwrap();
     br.knee
         .rotate_around(hip_center, back_top.interpolate(phase) * DEGREE, Axis::Z);
     br.hoof
         .rotate_around(hip_center, back_top.interpolate(phase) * DEGREE, Axis::Z);
     br.hoof.rotate_around(
-        &br.knee.center.borrow(),
+        &br.knee.center.read().unwrap(),
         back_bottom.interpolate(phase) * DEGREE,
         Axis::Z,
     );
 
-    let hip_center = &bl.hip.center.borrow();
+    let hip_center = &bl.hip.center.read().unwrap();
     bl.knee.rotate_around(
         hip_center,
         back_top.interpolate(phase - 0.44) * DEGREE,
@@ -185,7 +207,7 @@ fn walk(legs: &mut Legs, phase: f64) {
         Axis::Z,
     );
     bl.hoof.rotate_around(
-        &bl.knee.center.borrow(),
+        &bl.knee.center.read().unwrap(),
         back_bottom.interpolate(phase - 0.44) * DEGREE,
         Axis::Z,
     );
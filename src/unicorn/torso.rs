@@ -2,6 +2,7 @@ use crate::geometry::Axis;
 use crate::geometry::Bone;
 use crate::geometry::Vector;
 use crate::render::GroupTracer;
+use crate::render::TracerArena;
 use crate::render::WorldView;
 use crate::unicorn::Legs;
 use crate::unicorn::Neck;
@@ -24,11 +25,16 @@ impl Torso {
         }
     }
 
-    pub fn add_traceable(&self, mut tracer: &mut GroupTracer, world_view: &WorldView) {
-        self.neck.add_traceable(&mut tracer, world_view);
-        self.tail.add_traceable(&mut tracer, world_view);
-        self.torso.add_traceable(&mut tracer, world_view);
-        self.legs.add_traceable(&mut tracer, world_view);
+    pub fn add_traceable(
+        &self,
+        arena: &mut TracerArena,
+        mut tracer: &mut GroupTracer,
+        world_view: &WorldView,
+    ) {
+        self.neck.add_traceable(arena, &mut tracer, world_view);
+        self.tail.add_traceable(arena, &mut tracer, world_view);
+        self.torso.add_traceable(arena, &mut tracer, world_view);
+        self.legs.add_traceable(arena, &mut tracer, world_view);
     }
 
     pub fn rotate_around(&self, other: &Vector, angle: f64, axis: Axis) {
@@ -4,6 +4,7 @@ use crate::geometry::Bone;
 use crate::geometry::Gamma;
 use crate::geometry::Vector;
 use crate::render::GroupTracer;
+use crate::render::TracerArena;
 use crate::render::WorldView;
 use crate::unicorn::Head;
 use crate::unicorn::Legs;
@@ -70,7 +71,7 @@ impl Unicorn {
             horn_tip_color,
         );
         horn_tip.set_distance(data.horn_length, &horn_onset);
-        horn_tip.rotate_around(&horn_onset.center.borrow(), data.horn_angle, Axis::Z);
+        horn_tip.rotate_around(&horn_onset.center.read().unwrap(), data.horn_angle, Axis::Z);
 
         let eye_left = Ball::new(
             "left eye".into(),
@@ -183,7 +184,7 @@ impl Unicorn {
             tail_end_color,
         );
         tail_end.set_distance(data.tail_length, &tail_start);
-        tail_end.rotate_around(&tail_start.center.borrow(), data.tail_angle, Axis::Z);
+        tail_end.rotate_around(&tail_start.center.read().unwrap(), data.tail_angle, Axis::Z);
 
         let tail = Bone::non_linear_y(tail_start, tail_end, Gamma::new(data.tail_gamma, 0.3));
 
@@ -224,7 +225,7 @@ impl Unicorn {
         let neck = Bone::new(head.attachment(), shoulder.clone());
         let neck = Neck::new(head.clone(), neck, mane);
 
-        let pivot = &shoulder.center.borrow().clone();
+        let pivot = &shoulder.center.read().unwrap().clone();
         neck.rotate_around(pivot, data.neck_tilt, Axis::Y);
 
         let torso = Bone::new(shoulder.clone(), butt);
@@ -232,30 +233,34 @@ impl Unicorn {
 
         match data.pose {
             Pose::Walk { phase: _ } => {
-                let low_front = if legs.fl.hoof.center.borrow().y > legs.fr.hoof.center.borrow().y {
+                let low_front = if legs.fl.hoof.center.read().unwrap().y
+                    > legs.fr.hoof.center.read().unwrap().y
+                {
                     legs.fl.hoof.center
                 } else {
                     legs.fr.hoof.center
                 };
 
-                let low_back = if legs.bl.hoof.center.borrow().y > legs.br.hoof.center.borrow().y {
+                let low_back = if legs.bl.hoof.center.read().unwrap().y
+                    > legs.br.hoof.center.read().unwrap().y
+                {
                     legs.bl.hoof.center
                 } else {
                     legs.br.hoof.center
                 };
 
-                let angle = ((low_back.borrow().y - low_front.borrow().y)
-                    / (low_back.borrow().x - low_front.borrow().x))
+                let angle = ((low_back.read().unwrap().y - low_front.read().unwrap().y)
+                    / (low_back.read().unwrap().x - low_front.read().unwrap().x))
                     .atan();
 
-                let pivot = shoulder.center.borrow().clone();
+                let pivot = shoulder.center.read().unwrap().clone();
                 torso.rotate_around(&pivot, -angle, Axis::Z);
             }
             Pose::RotaryGallop { phase: _ } => {}
         }
 
         if data.x_angle < 0.0 {
-            let pivot = &shoulder.center.borrow().clone();
+            let pivot = &shoulder.center.read().unwrap().clone();
             torso.rotate_around(pivot, data.y_angle, Axis::Y);
             torso.rotate_around(pivot, data.x_angle, Axis::X);
             torso.rotate_around(pivot, -data.y_angle, Axis::Y);
@@ -274,10 +279,10 @@ impl Unicorn {
         self.torso.torso.b1.clone()
     }
 
-    pub fn tracer(&self, world_view: &WorldView) -> GroupTracer {
+    pub fn tracer(&self, arena: &mut TracerArena, world_view: &WorldView) -> GroupTracer {
         let mut tracer = GroupTracer::new();
 
-        self.torso.add_traceable(&mut tracer, world_view);
+        self.torso.add_traceable(arena, &mut tracer, world_view);
 
         tracer
     }
@@ -0,0 +1,148 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use image::Rgba;
+use image::RgbaImage;
+
+use std::io::Write;
+
+/// Y4mWriter writes frames as uncompressed YUV4MPEG2 video that ffmpeg and other encoders read
+/// from a file or a pipe.
+///
+/// Frames are converted to BT.601 limited range YUV with 4:2:0 chroma subsampling.  Y4M has no
+/// alpha channel so transparent pixels are composited over black.
+///
+///     use unicornify::Y4mWriter;
+///     use image::RgbaImage;
+///
+///     let mut video = Vec::new();
+///     let mut writer = Y4mWriter::new(&mut video, 4, 4, 25, 1).unwrap();
+///
+///     writer.write_frame(&RgbaImage::new(4, 4)).unwrap();
+pub struct Y4mWriter<W: Write> {
+    writer: W,
+    width: u32,
+    height: u32,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    /// Writes the stream header for +width+ by +height+ frames shown at +rate_numerator+ /
+    /// +rate_denominator+ frames per second
+    pub fn new(
+        writer: W,
+        width: u32,
+        height: u32,
+        rate_numerator: u32,
+        rate_denominator: u32,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 {
+            bail!("Y4M frames must be at least 1 pixel wide and high");
+        }
+
+        if rate_numerator == 0 || rate_denominator == 0 {
+            bail!(
+                "Invalid Y4M frame rate {}:{}",
+                rate_numerator,
+                rate_denominator
+            );
+        }
+
+        let mut writer = writer;
+
+        writeln!(
+            writer,
+            "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C420jpeg",
+            width, height, rate_numerator, rate_denominator
+        )
+        .context("Unable to write Y4M header")?;
+
+        Ok(Y4mWriter {
+            writer,
+            width,
+            height,
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &RgbaImage) -> Result<()> {
+        if frame.dimensions() != (self.width, self.height) {
+            bail!(
+                "Frame is {}x{} but the Y4M stream is {}x{}",
+                frame.width(),
+                frame.height(),
+                self.width,
+                self.height
+            );
+        }
+
+        let chroma_width = self.width.div_ceil(2);
+        let chroma_height = self.height.div_ceil(2);
+
+        let mut y_plane = Vec::with_capacity((self.width * self.height) as usize);
+        let mut u_plane = Vec::with_capacity((chroma_width * chroma_height) as usize);
+        let mut v_plane = Vec::with_capacity((chroma_width * chroma_height) as usize);
+
+        for pixel in frame.pixels() {
+            let (r, g, b) = over_black(pixel);
+
+            y_plane.push(to_byte(
+                16.0 + (65.481 * r + 128.553 * g + 24.966 * b) / 255.0,
+            ));
+        }
+
+        for cy in 0..chroma_height {
+            for cx in 0..chroma_width {
+                let (mut r, mut g, mut b, mut n) = (0.0, 0.0, 0.0, 0.0);
+
+                for y in cy * 2..(cy * 2 + 2).min(self.height) {
+                    for x in cx * 2..(cx * 2 + 2).min(self.width) {
+                        let (pr, pg, pb) = over_black(frame.get_pixel(x, y));
+
+                        r += pr;
+                        g += pg;
+                        b += pb;
+                        n += 1.0;
+                    }
+                }
+
+                let (r, g, b) = (r / n, g / n, b / n);
+
+                u_plane.push(to_byte(
+                    128.0 + (-37.797 * r - 74.203 * g + 112.0 * b) / 255.0,
+                ));
+                v_plane.push(to_byte(
+                    128.0 + (112.0 * r - 93.786 * g - 18.214 * b) / 255.0,
+                ));
+            }
+        }
+
+        self.writer
+            .write_all(b"FRAME\n")
+            .and_then(|_| self.writer.write_all(&y_plane))
+            .and_then(|_| self.writer.write_all(&u_plane))
+            .and_then(|_| self.writer.write_all(&v_plane))
+            .context("Unable to write Y4M frame")
+    }
+
+    /// Flushes and returns the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush().context("Unable to write Y4M video")?;
+
+        Ok(self.writer)
+    }
+}
+
+// The color of +pixel+ drawn over black
+fn over_black(pixel: &Rgba<u8>) -> (f64, f64, f64) {
+    let alpha = pixel[3] as f64 / 255.0;
+
+    (
+        pixel[0] as f64 * alpha,
+        pixel[1] as f64 * alpha,
+        pixel[2] as f64 * alpha,
+    )
+}
+
+fn to_byte(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}